@@ -0,0 +1,403 @@
+use crate::blake2t::{Blake2bParams, Blake2sParams};
+use crate::hasher::SyncDigest;
+use crate::{blake3t, mac, xof::Xof};
+use digest::{Digest, DynDigest};
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum HashAlgorithm {
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+    /// SHA-3's extendable output functions, any length
+    Shake128,
+    Shake256,
+    /// SHAKE with a customization string
+    CShake128,
+    CShake256,
+    /// KangarooTwelve, a faster Keccak XOF with a customization string
+    K12,
+    Sha2_224,
+    Sha2_256,
+    Sha2_384,
+    Sha2_512,
+    /// SHA-512 truncated to 224 bits
+    Sha2_512T224,
+    /// SHA-512 truncated to 256 bits
+    Sha2_512T256,
+    /// Also parsed from blake2-256, blake2-384 and blake2-512, which older
+    /// versions made BLAKE2s on 32-bit targets
+    Blake2b256,
+    Blake2b384,
+    Blake2b512,
+    /// BLAKE2b at 1 to 64 bytes
+    Blake2b,
+    Blake2s128,
+    Blake2s256,
+    /// BLAKE2s at 1 to 32 bytes
+    Blake2s,
+    Blake3_256,
+    Blake3_384,
+    Blake3_512,
+    /// BLAKE3 at any length
+    Blake3,
+    Whirlpool,
+    Sha1,
+    Ripemd320,
+    Ripemd160,
+    Ripemd128,
+    Md5,
+}
+
+use HashAlgorithm::*;
+
+impl HashAlgorithm {
+    pub fn parse<I: AsRef<str>>(s: I) -> Result<Self, String> {
+        let s = s.as_ref();
+        HashAlgorithm::values()
+            .into_iter()
+            .find(|a| a.name() == s || a.aliases().contains(&s))
+            .ok_or_else(|| format!("Unknown hash algorithm: {}", s))
+    }
+
+    /// The name used by `Display` and shown by the command line
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Sha3_224 => "sha3-224",
+            Sha3_256 => "sha3-256",
+            Sha3_384 => "sha3-384",
+            Sha3_512 => "sha3-512",
+            Shake128 => "shake128",
+            Shake256 => "shake256",
+            CShake128 => "cshake128",
+            CShake256 => "cshake256",
+            K12 => "k12",
+            Sha2_224 => "sha2-224",
+            Sha2_256 => "sha2-256",
+            Sha2_384 => "sha2-384",
+            Sha2_512 => "sha2-512",
+            Sha2_512T224 => "sha2-512-t224",
+            Sha2_512T256 => "sha2-512-t256",
+            Blake2b256 => "blake2b-256",
+            Blake2b384 => "blake2b-384",
+            Blake2b512 => "blake2b-512",
+            Blake2b => "blake2b",
+            Blake2s128 => "blake2s-128",
+            Blake2s256 => "blake2s-256",
+            Blake2s => "blake2s",
+            Blake3_256 => "blake3-256",
+            Blake3_384 => "blake3-384",
+            Blake3_512 => "blake3-512",
+            Blake3 => "blake3",
+            Whirlpool => "whirlpool",
+            Sha1 => "sha1",
+            Ripemd320 => "ripemd320",
+            Ripemd160 => "ripemd160",
+            Ripemd128 => "ripemd128",
+            Md5 => "md5",
+        }
+    }
+
+    /// Other names accepted when parsing
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match *self {
+            // Earlier versions misspelled it
+            Whirlpool => &["whirpool"],
+            K12 => &["kangarootwelve"],
+            Blake2b256 => &["blake2-256"],
+            Blake2b384 => &["blake2-384"],
+            Blake2b512 => &["blake2-512"],
+            _ => &[],
+        }
+    }
+
+    /// The digest length in bytes, the default length for extendable algorithms
+    pub fn output_size(&self) -> usize {
+        match *self {
+            Md5 | Ripemd128 | Blake2s128 => 16,
+            Sha1 | Ripemd160 => 20,
+            Sha2_224 | Sha2_512T224 | Sha3_224 => 28,
+            Sha2_256 | Sha2_512T256 | Sha3_256 | Blake3_256 | Blake2b256 => 32,
+            Shake128 | CShake128 | K12 | Blake3 | Blake2s256 | Blake2s => 32,
+            Ripemd320 => 40,
+            Sha2_384 | Sha3_384 | Blake3_384 | Blake2b384 => 48,
+            Sha2_512 | Sha3_512 | Blake3_512 | Whirlpool | Shake256 | CShake256 => 64,
+            Blake2b512 | Blake2b => 64,
+        }
+    }
+
+    /// MD5, SHA-1 and RIPEMD are no longer considered secure
+    pub fn is_legacy(&self) -> bool {
+        matches!(self, Md5 | Sha1 | Ripemd320 | Ripemd160 | Ripemd128)
+    }
+
+    pub fn is_blake3(&self) -> bool {
+        matches!(self, Blake3_256 | Blake3_384 | Blake3_512 | Blake3)
+    }
+
+    /// The BLAKE2b and BLAKE2s algorithms, which take a key, salt and
+    /// personalization
+    pub fn is_blake2(&self) -> bool {
+        matches!(
+            self,
+            Blake2b256 | Blake2b384 | Blake2b512 | Blake2b | Blake2s128 | Blake2s256 | Blake2s
+        )
+    }
+
+    /// The digest lengths in bytes the algorithm can give, only its output
+    /// size unless it's extendable or BLAKE2b or BLAKE2s at any length
+    pub fn lengths(&self) -> RangeInclusive<usize> {
+        match *self {
+            Blake2b => 1..=64,
+            Blake2s => 1..=32,
+            a if a.is_extendable() => 1..=usize::MAX,
+            a => a.output_size()..=a.output_size(),
+        }
+    }
+
+    /// Extendable output functions give digests of any length
+    pub fn is_extendable(&self) -> bool {
+        matches!(
+            self,
+            Shake128 | Shake256 | CShake128 | CShake256 | K12 | Blake3
+        )
+    }
+
+    /// cSHAKE and KangarooTwelve take a customization string
+    pub fn is_customizable(&self) -> bool {
+        matches!(self, CShake128 | CShake256 | K12)
+    }
+
+    /// A new hash computation for this algorithm
+    pub fn hasher(&self) -> Box<dyn DynDigest> {
+        self.sync_hasher()
+    }
+
+    pub(crate) fn sync_hasher(&self) -> Box<dyn SyncDigest> {
+        match *self {
+            Sha3_224 => Box::new(sha3::Sha3_224::new()),
+            Sha3_256 => Box::new(sha3::Sha3_256::new()),
+            Sha3_384 => Box::new(sha3::Sha3_384::new()),
+            Sha3_512 => Box::new(sha3::Sha3_512::new()),
+            Sha2_224 => Box::new(sha2::Sha224::new()),
+            Sha2_256 => Box::new(sha2::Sha256::new()),
+            Sha2_384 => Box::new(sha2::Sha384::new()),
+            Sha2_512 => Box::new(sha2::Sha512::new()),
+            Sha2_512T224 => Box::new(sha2::Sha512_224::new()),
+            Sha2_512T256 => Box::new(sha2::Sha512_256::new()),
+            a @ (Blake2b256 | Blake2b384 | Blake2b512 | Blake2b | Blake2s128 | Blake2s256
+            | Blake2s) => a.sync_blake2(a.output_size(), b"", b"", b"").unwrap(),
+            Blake3_256 => Box::new(blake3t::Blake3_256::new()),
+            Blake3_384 => Box::new(blake3t::Blake3_384::new()),
+            Blake3_512 => Box::new(blake3t::Blake3_512::new()),
+            a @ (Shake128 | Shake256 | CShake128 | CShake256 | K12 | Blake3) => {
+                Box::new(Xof::new(a, a.output_size(), b"").unwrap())
+            }
+            Whirlpool => Box::new(whirlpool::Whirlpool::new()),
+            Sha1 => Box::new(sha1::Sha1::new()),
+            Ripemd320 => Box::new(ripemd::Ripemd320::new()),
+            Ripemd160 => Box::new(ripemd::Ripemd160::new()),
+            Ripemd128 => Box::new(ripemd::Ripemd128::new()),
+            Md5 => Box::new(md5::Md5::new()),
+        }
+    }
+
+    /// A new computation of `len` bytes for an extendable algorithm, `None`
+    /// for the others. The customization string only changes cSHAKE and
+    /// KangarooTwelve.
+    pub fn xof(&self, len: usize, customization: &[u8]) -> Option<Box<dyn DynDigest>> {
+        let xof: Box<dyn DynDigest> = Box::new(Xof::new(*self, len, customization)?);
+        Some(xof)
+    }
+
+    /// A new BLAKE2b or BLAKE2s computation of `len` bytes with a key, salt
+    /// and personalization, each of which can be empty. Fails for other
+    /// algorithms and for lengths and parameters BLAKE2 doesn't take.
+    pub fn blake2(
+        &self,
+        len: usize,
+        key: &[u8],
+        salt: &[u8],
+        personal: &[u8],
+    ) -> Result<Box<dyn DynDigest>, String> {
+        let blake2: Box<dyn DynDigest> = self.sync_blake2(len, key, salt, personal)?;
+        Ok(blake2)
+    }
+
+    pub(crate) fn sync_blake2(
+        &self,
+        len: usize,
+        key: &[u8],
+        salt: &[u8],
+        personal: &[u8],
+    ) -> Result<Box<dyn SyncDigest>, String> {
+        if self.is_blake2() && !matches!(self, Blake2b | Blake2s) && len != self.output_size() {
+            return Err(format!(
+                "{} digests are {} bytes not {}",
+                self,
+                self.output_size(),
+                len
+            ));
+        }
+        match *self {
+            Blake2b256 | Blake2b384 | Blake2b512 | Blake2b => {
+                Ok(Box::new(Blake2bParams::new(len, key, salt, personal)?))
+            }
+            Blake2s128 | Blake2s256 | Blake2s => {
+                Ok(Box::new(Blake2sParams::new(len, key, salt, personal)?))
+            }
+            a => Err(format!("{} has no key, salt or personalization", a)),
+        }
+    }
+
+    /// A new keyed computation, HMAC for SHA-1, SHA-2, RIPEMD, Whirlpool and
+    /// MD5, KMAC128 and KMAC256 for SHA-3, or KMACXOF for SHAKE and cSHAKE.
+    /// BLAKE2, BLAKE3 and KangarooTwelve have none.
+    pub fn mac(&self, key: &[u8]) -> Result<Box<dyn DynDigest>, String> {
+        let mac: Box<dyn DynDigest> = mac::mac(*self, key, self.output_size(), b"")?;
+        Ok(mac)
+    }
+
+    /// Compute the digest of `data` in one call
+    pub fn digest<T: AsRef<[u8]>>(&self, data: T) -> Vec<u8> {
+        let mut hasher = self.hasher();
+        hasher.update(data.as_ref());
+        hasher.finalize().into_vec()
+    }
+
+    /// The algorithms whose digests are `len` bytes long
+    pub fn with_output_size(len: usize) -> Vec<Self> {
+        let mut values = HashAlgorithm::values();
+        values.retain(|a| a.output_size() == len);
+        values
+    }
+
+    /// The algorithms that could have produced a `len` byte digest, those in
+    /// [`HashAlgorithm::with_output_size`] and, for digests of at least 16
+    /// bytes, those that can give that length. Shorter ones are too likely to
+    /// match by chance.
+    pub fn for_digest_length(len: usize) -> Vec<Self> {
+        let mut values = HashAlgorithm::values();
+        values.retain(|a| a.output_size() == len || (a.lengths().contains(&len) && len >= 16));
+        values
+    }
+
+    pub fn values() -> Vec<Self> {
+        vec![
+            Sha3_224,
+            Sha3_256,
+            Sha3_384,
+            Sha3_512,
+            Shake128,
+            Shake256,
+            CShake128,
+            CShake256,
+            K12,
+            Sha2_224,
+            Sha2_256,
+            Sha2_384,
+            Sha2_512,
+            Sha2_512T224,
+            Sha2_512T256,
+            Blake2b256,
+            Blake2b384,
+            Blake2b512,
+            Blake2b,
+            Blake2s128,
+            Blake2s256,
+            Blake2s,
+            Blake3_256,
+            Blake3_384,
+            Blake3_512,
+            Blake3,
+            Whirlpool,
+            Sha1,
+            Ripemd320,
+            Ripemd160,
+            Ripemd128,
+            Md5,
+        ]
+    }
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl From<&str> for HashAlgorithm {
+    fn from(s: &str) -> Self {
+        HashAlgorithm::parse(s).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serious::Encoding;
+
+    #[test]
+    fn names() {
+        for a in HashAlgorithm::values() {
+            assert_eq!(HashAlgorithm::parse(a.name()).unwrap(), a);
+            assert_eq!(HashAlgorithm::parse(a.to_string()).unwrap(), a);
+            assert_eq!(a.digest(b"").len(), a.output_size(), "{}", a);
+        }
+        assert_eq!(HashAlgorithm::parse("whirpool").unwrap(), Whirlpool);
+        assert!(HashAlgorithm::parse("sha4").is_err());
+        assert_eq!(HashAlgorithm::with_output_size(20), vec![Sha1, Ripemd160]);
+        assert_eq!(HashAlgorithm::for_digest_length(20).len(), 10);
+        assert!(HashAlgorithm::for_digest_length(40).contains(&Blake2b));
+        assert!(!HashAlgorithm::for_digest_length(40).contains(&Blake2s));
+        assert_eq!(HashAlgorithm::parse("kangarootwelve").unwrap(), K12);
+        assert_eq!(HashAlgorithm::parse("blake2-256").unwrap(), Blake2b256);
+        assert_eq!(HashAlgorithm::parse("blake2-512").unwrap(), Blake2b512);
+        assert_eq!(Blake2b512.output_size(), 64);
+        assert_eq!(HashAlgorithm::for_digest_length(100).len(), 6);
+        assert!(HashAlgorithm::for_digest_length(8).is_empty());
+        assert_eq!(Shake256.xof(100, b"").unwrap().output_size(), 100);
+        assert!(Sha2_256.xof(100, b"").is_none());
+    }
+
+    #[test]
+    fn digests() {
+        let cases = [
+            (Md5, "900150983cd24fb0d6963f7d28e17f72"),
+            (Sha1, "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (
+                Sha2_256,
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                Sha3_256,
+                "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            ),
+            (
+                Blake3_256,
+                "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+            ),
+            (Ripemd160, "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+            (
+                Blake2b512,
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+                 7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+            ),
+            (
+                Blake2s256,
+                "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+            ),
+        ];
+        for (a, expected) in cases {
+            let digest = a.digest(b"abc");
+            assert_eq!(
+                Encoding::encode(digest, Encoding::LowHex).into_string(),
+                expected,
+                "{}",
+                a
+            );
+        }
+    }
+}
@@ -1,127 +1,111 @@
-#[cfg(target_pointer_width = "64")]
-use blake2::Blake2bVar as VarBlake2;
-#[cfg(target_pointer_width = "32")]
-use blake2::Blake2sVar as VarBlake2;
-
 use blake2::digest::Output;
 use blake2::{
     digest::{
-        generic_array::typenum::{U32, U48},
-        FixedOutput, FixedOutputReset, OutputSizeUser, Reset, Update, VariableOutput,
+        core_api::{Buffer, UpdateCore, VariableOutputCore},
+        DynDigest, InvalidBufferSize,
     },
-    Digest,
+    Blake2bVarCore, Blake2sVarCore,
 };
 
-macro_rules! blake2 {
-    ($name:ident, $size:expr, $outputsize:ident) => {
-        #[derive(Debug, Clone)]
-        pub struct $name(VarBlake2);
-
-        impl FixedOutput for $name {
-            fn finalize_into(self, out: &mut Output<Self>) {
-                self.0.finalize_variable(out).expect("invalid output size");
-            }
+/// BLAKE2b or BLAKE2s with a runtime output length, key, salt and
+/// personalization, the same on every platform
+macro_rules! blake2_params {
+    ($name:ident, $core:ident, $alg:expr, $max:expr) => {
+        #[derive(Clone)]
+        pub(crate) struct $name {
+            initial: ($core, Buffer<$core>),
+            core: $core,
+            buffer: Buffer<$core>,
+            len: usize,
         }
 
-        impl FixedOutputReset for $name {
-            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
-                self.0
-                    .clone()
-                    .finalize_variable(out)
-                    .expect("reader read failed");
-                <Self as Reset>::reset(self);
+        impl $name {
+            /// Fails for a length, key, salt or personalization longer than
+            /// the variant takes
+            pub(crate) fn new(
+                len: usize,
+                key: &[u8],
+                salt: &[u8],
+                personal: &[u8],
+            ) -> Result<Self, String> {
+                if len == 0 || len > $max {
+                    return Err(format!("{} digests are 1 to {} bytes", $alg, $max));
+                }
+                if key.len() > $max {
+                    return Err(format!("{} keys are at most {} bytes", $alg, $max));
+                }
+                if salt.len() > $max / 4 || personal.len() > $max / 4 {
+                    return Err(format!(
+                        "{} salts and personalizations are at most {} bytes",
+                        $alg,
+                        $max / 4
+                    ));
+                }
+                let core = $core::new_with_params(salt, personal, key.len(), len);
+                // A key is the first block, padded with zeros
+                let buffer = match key.is_empty() {
+                    true => Buffer::<$core>::default(),
+                    false => {
+                        let mut block = [0u8; $max * 2];
+                        block[..key.len()].copy_from_slice(key);
+                        Buffer::<$core>::new(&block)
+                    }
+                };
+                Ok($name {
+                    initial: (core.clone(), buffer.clone()),
+                    core,
+                    buffer,
+                    len,
+                })
             }
-        }
-
-        impl OutputSizeUser for $name {
-            type OutputSize = $outputsize;
-        }
 
-        impl Update for $name {
-            fn update(&mut self, data: &[u8]) {
-                self.0.update(data);
-            }
-        }
-
-        impl Reset for $name {
-            fn reset(&mut self) {
-                self.0 = VarBlake2::new($size).unwrap();
+            fn finish(mut core: $core, mut buffer: Buffer<$core>, out: &mut [u8]) {
+                let mut full = Output::<$core>::default();
+                core.finalize_variable_core(&mut buffer, &mut full);
+                out.copy_from_slice(&full[..out.len()]);
             }
         }
 
-        impl Digest for $name {
-            fn new() -> Self {
-                $name(VarBlake2::new($size).unwrap())
-            }
-
-            fn new_with_prefix(data: impl AsRef<[u8]>) -> Self {
-                let mut hasher = Self::new();
-                hasher.0.update(data.as_ref());
-                hasher
-            }
-
-            fn update(&mut self, data: impl AsRef<[u8]>) {
-                self.0.update(data.as_ref());
-            }
-
-            fn chain_update(self, data: impl AsRef<[u8]>) -> Self {
-                let mut hasher = self.0.clone();
-                hasher.update(data.as_ref());
-                Self(hasher)
-            }
-
-            fn finalize(self) -> Output<Self> {
-                let mut res = Output::<Self>::default();
-                self.0.finalize_variable(&mut res).expect("finalize failed");
-                res
+        impl DynDigest for $name {
+            fn update(&mut self, data: &[u8]) {
+                let core = &mut self.core;
+                self.buffer
+                    .digest_blocks(data, |blocks| core.update_blocks(blocks));
             }
 
-            fn finalize_reset(&mut self) -> Output<Self>
-            where
-                Self: FixedOutputReset,
-            {
-                let mut res = Output::<Self>::default();
-                self.0
-                    .clone()
-                    .finalize_variable(&mut res)
-                    .expect("finalize failed");
-                <Self as Reset>::reset(self);
-                res
+            fn finalize_into(self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+                if buf.len() != self.len {
+                    return Err(InvalidBufferSize);
+                }
+                $name::finish(self.core, self.buffer, buf);
+                Ok(())
             }
 
-            fn finalize_into(self, out: &mut Output<Self>) {
-                self.0.finalize_variable(out).expect("finalize failed");
+            fn finalize_into_reset(&mut self, out: &mut [u8]) -> Result<(), InvalidBufferSize> {
+                if out.len() != self.len {
+                    return Err(InvalidBufferSize);
+                }
+                let (core, buffer) = self.initial.clone();
+                let core = std::mem::replace(&mut self.core, core);
+                let buffer = std::mem::replace(&mut self.buffer, buffer);
+                $name::finish(core, buffer, out);
+                Ok(())
             }
 
             fn reset(&mut self) {
-                self.0 = VarBlake2::new($size).unwrap();
-            }
-
-            fn finalize_into_reset(&mut self, out: &mut Output<Self>)
-            where
-                Self: FixedOutputReset,
-            {
-                self.0
-                    .clone()
-                    .finalize_variable(out)
-                    .expect("finalize failed");
-                <Self as Reset>::reset(self)
+                (self.core, self.buffer) = self.initial.clone();
             }
 
-            fn output_size() -> usize {
-                $size
+            fn output_size(&self) -> usize {
+                self.len
             }
 
-            fn digest(data: impl AsRef<[u8]>) -> Output<Self> {
-                let mut res = Output::<Self>::default();
-                let mut b = Self::new();
-                <Self as Digest>::update(&mut b, data);
-                <Self as FixedOutput>::finalize_into(b, &mut res);
-                res
+            fn box_clone(&self) -> Box<dyn DynDigest> {
+                Box::new(self.clone())
             }
         }
     };
 }
 
-blake2!(Blake2_256, 32, U32);
-blake2!(Blake2_384, 48, U48);
+blake2_params!(Blake2bParams, Blake2bVarCore, "BLAKE2b", 64);
+blake2_params!(Blake2sParams, Blake2sVarCore, "BLAKE2s", 32);
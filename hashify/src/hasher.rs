@@ -0,0 +1,425 @@
+use crate::xof::Xof;
+use crate::HashAlgorithm;
+use digest::DynDigest;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// Bytes read from the input at a time
+const BUFFER: usize = 65536;
+
+/// BLAKE3 is slower on many threads below this
+const RAYON_MIN: usize = 131072;
+
+/// A digest that can be cloned and shared between threads
+pub(crate) trait SyncDigest: DynDigest + Send + Sync {
+    fn clone_sync(&self) -> Box<dyn SyncDigest>;
+}
+
+impl<T: DynDigest + Clone + Send + Sync + 'static> SyncDigest for T {
+    fn clone_sync(&self) -> Box<dyn SyncDigest> {
+        Box::new(self.clone())
+    }
+}
+
+enum State {
+    Digest(Box<dyn SyncDigest>),
+    /// Kept as blake3's own hasher to use its multithreaded update
+    Blake3(Box<blake3::Hasher>),
+}
+
+/// Each algorithm with the digest it produced
+pub type Digests = Vec<(HashAlgorithm, Vec<u8>)>;
+
+impl Clone for State {
+    fn clone(&self) -> Self {
+        match self {
+            State::Digest(hasher) => State::Digest(hasher.clone_sync()),
+            State::Blake3(hasher) => State::Blake3(hasher.clone()),
+        }
+    }
+}
+
+/// Feeds each read of the input into the digests of many algorithms at once
+/// so a file is only read once no matter how many checksums are wanted
+#[derive(Clone)]
+pub struct MultiHasher {
+    /// Each algorithm with the length of its digest
+    hashers: Vec<(HashAlgorithm, usize, State)>,
+    customization: Vec<u8>,
+    hmac_key: Option<Vec<u8>>,
+    /// A new BLAKE3 hasher in the mode to use
+    blake3: blake3::Hasher,
+    blake2_key: Vec<u8>,
+    salt: Vec<u8>,
+    personal: Vec<u8>,
+}
+
+impl MultiHasher {
+    pub fn new(algorithms: &[HashAlgorithm]) -> Self {
+        let sizes = algorithms
+            .iter()
+            .map(|a| (*a, a.output_size()))
+            .collect::<Vec<_>>();
+        MultiHasher::sized(&sizes).unwrap()
+    }
+
+    /// Each algorithm with its own digest length, which can differ from its
+    /// output size for those in [`HashAlgorithm::lengths`]. An algorithm can
+    /// be given more than once.
+    pub fn sized(algorithms: &[(HashAlgorithm, usize)]) -> Result<Self, String> {
+        for (a, len) in algorithms {
+            if !a.lengths().contains(len) {
+                return Err(format!("{} can't give a {} byte digest", a, len));
+            }
+        }
+        let hasher = MultiHasher {
+            hashers: algorithms
+                .iter()
+                .map(|(a, len)| (*a, *len, State::Blake3(Box::default())))
+                .collect(),
+            customization: Vec::new(),
+            hmac_key: None,
+            blake3: blake3::Hasher::new(),
+            blake2_key: Vec::new(),
+            salt: Vec::new(),
+            personal: Vec::new(),
+        };
+        hasher.rebuild()
+    }
+
+    /// Give `len` byte digests from algorithms that can, like SHAKE,
+    /// KangarooTwelve, BLAKE3 and BLAKE2b. Others keep their length.
+    pub fn with_length(mut self, len: usize) -> Self {
+        for (a, size, _) in self.hashers.iter_mut() {
+            if a.lengths().contains(&len) {
+                *size = len;
+            }
+        }
+        self.rebuild().expect("unchanged keys")
+    }
+
+    /// The customization string for cSHAKE and KangarooTwelve, like an
+    /// application name, so the same input hashes differently elsewhere
+    pub fn with_customization(mut self, customization: &[u8]) -> Self {
+        self.customization = customization.to_vec();
+        self.rebuild().expect("unchanged keys")
+    }
+
+    /// Make BLAKE3 a MAC with a 32 byte key, its `keyed_hash` mode
+    pub fn with_blake3_key(mut self, key: &[u8; 32]) -> Self {
+        self.blake3 = blake3::Hasher::new_keyed(key);
+        self.rebuild().expect("unchanged keys")
+    }
+
+    /// Make BLAKE3 derive a key from the input for `context`, its `derive_key`
+    /// mode. The context should be hardcoded, unique to the application and
+    /// say what the key is for.
+    pub fn with_blake3_context(mut self, context: &str) -> Self {
+        self.blake3 = blake3::Hasher::new_derive_key(context);
+        self.rebuild().expect("unchanged keys")
+    }
+
+    /// Make BLAKE2b and BLAKE2s MACs with a key up to 64 or 32 bytes
+    pub fn with_blake2_key(mut self, key: &[u8]) -> Result<Self, String> {
+        self.blake2_key = key.to_vec();
+        self.rebuild()
+    }
+
+    /// The BLAKE2b or BLAKE2s salt, up to 16 or 8 bytes
+    pub fn with_blake2_salt(mut self, salt: &[u8]) -> Result<Self, String> {
+        self.salt = salt.to_vec();
+        self.rebuild()
+    }
+
+    /// The BLAKE2b or BLAKE2s personalization, up to 16 or 8 bytes, like
+    /// the customization string of cSHAKE
+    pub fn with_blake2_personal(mut self, personal: &[u8]) -> Result<Self, String> {
+        self.personal = personal.to_vec();
+        self.rebuild()
+    }
+
+    /// Make every algorithm a MAC with `key`, HMAC, KMAC for SHA-3 or
+    /// KMACXOF for SHAKE and cSHAKE. Fails for BLAKE2 and BLAKE3 which have
+    /// their own keyed modes, and for KangarooTwelve.
+    pub fn with_hmac_key(mut self, key: &[u8]) -> Result<Self, String> {
+        self.hmac_key = Some(key.to_vec());
+        self.rebuild()
+    }
+
+    /// New states for every algorithm with the current lengths and keys
+    fn rebuild(mut self) -> Result<Self, String> {
+        for i in 0..self.hashers.len() {
+            let (a, len, _) = self.hashers[i];
+            self.hashers[i].2 = self.state(a, len)?;
+        }
+        Ok(self)
+    }
+
+    fn state(&self, a: HashAlgorithm, len: usize) -> Result<State, String> {
+        if let Some(key) = &self.hmac_key {
+            let mac = crate::mac::mac(a, key, len, &self.customization)?;
+            return Ok(State::Digest(mac));
+        }
+        if a.is_blake3() {
+            return Ok(State::Blake3(Box::new(self.blake3.clone())));
+        }
+        if a.is_blake2() {
+            let blake2 = a.sync_blake2(len, &self.blake2_key, &self.salt, &self.personal)?;
+            return Ok(State::Digest(blake2));
+        }
+        Ok(State::Digest(match Xof::new(a, len, &self.customization) {
+            Some(xof) => Box::new(xof),
+            None => a.sync_hasher(),
+        }))
+    }
+
+    pub fn algorithms(&self) -> Vec<HashAlgorithm> {
+        self.hashers.iter().map(|(a, _, _)| *a).collect()
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for (_, _, state) in self.hashers.iter_mut() {
+            match state {
+                State::Digest(hasher) => hasher.update(data),
+                State::Blake3(hasher) if data.len() >= RAYON_MIN => {
+                    hasher.update_rayon(data);
+                }
+                State::Blake3(hasher) => {
+                    hasher.update(data);
+                }
+            }
+        }
+    }
+
+    /// Hash everything from `reader` returning the number of bytes read
+    pub fn update_reader<R: Read>(&mut self, reader: &mut R) -> io::Result<u64> {
+        let mut buffer = vec![0u8; BUFFER];
+        let mut total = 0;
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => return Ok(total),
+                Ok(n) => {
+                    self.update(&buffer[..n]);
+                    total += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Hash a file returning its size. Large regular files are memory mapped
+    /// once and every algorithm hashes the map, BLAKE3 on every thread of the
+    /// rayon pool. Anything that can't be mapped, like a pipe, is read like
+    /// [`MultiHasher::update_reader`].
+    pub fn update_path<P: AsRef<Path>>(&mut self, path: P) -> io::Result<u64> {
+        let mut file = File::open(path)?;
+        match map(&file) {
+            Some(map) => {
+                self.update(&map);
+                Ok(map.len() as u64)
+            }
+            None => self.update_reader(&mut file),
+        }
+    }
+
+    /// The digest from each algorithm in the order they were given
+    pub fn finalize(self) -> Digests {
+        self.hashers
+            .into_iter()
+            .map(|(a, len, state)| match state {
+                State::Digest(hasher) => (a, hasher.finalize().into_vec()),
+                State::Blake3(hasher) => {
+                    let mut digest = vec![0u8; len];
+                    hasher.finalize_xof().fill(&mut digest);
+                    (a, digest)
+                }
+            })
+            .collect()
+    }
+}
+
+/// A map of a regular file bigger than one read, which is no faster to map
+#[allow(unsafe_code)]
+fn map(file: &File) -> Option<Mmap> {
+    let metadata = file.metadata().ok()?;
+    if !metadata.is_file() || metadata.len() <= BUFFER as u64 {
+        return None;
+    }
+    // SAFETY: the map is only read while hashing. Like `b3sum`, a file
+    // changed by another process meanwhile gives a meaningless digest.
+    unsafe { Mmap::map(file) }.ok()
+}
+
+impl Write for MultiHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashAlgorithm::*;
+    use digest::{Update, VariableOutput};
+
+    /// Long enough for more than one read and BLAKE3 on many threads
+    fn data() -> Vec<u8> {
+        vec![7u8; BUFFER * 2 + 3]
+    }
+
+    #[test]
+    fn multi_hasher() {
+        let data = data();
+        let algorithms = [Sha2_256, Blake3_512, Md5];
+        let mut hasher = MultiHasher::new(&algorithms);
+        assert_eq!(hasher.algorithms(), algorithms);
+        assert_eq!(
+            hasher.update_reader(&mut data.as_slice()).unwrap(),
+            data.len() as u64
+        );
+        for (a, digest) in hasher.finalize() {
+            assert_eq!(digest, a.digest(&data), "{}", a);
+        }
+
+        let mut hasher = MultiHasher::new(&[Sha1]);
+        io::copy(&mut &b"abc"[..], &mut hasher).unwrap();
+        assert_eq!(hasher.finalize()[0].1, Sha1.digest(b"abc"));
+    }
+
+    #[test]
+    fn update_path() {
+        let data = data();
+        let path = std::env::temp_dir().join(format!("hashify-hasher-{}", std::process::id()));
+        // Mapped and read
+        for data in [&data[..], &data[..BUFFER]] {
+            std::fs::write(&path, data).unwrap();
+            for algorithms in [
+                &[Sha2_256, Blake3_512, Md5][..],
+                &[Blake3_256, Blake3_384],
+                &[Md5],
+            ] {
+                let mut hasher = MultiHasher::new(algorithms);
+                assert_eq!(hasher.update_path(&path).unwrap(), data.len() as u64);
+                for (a, digest) in hasher.finalize() {
+                    assert_eq!(digest, a.digest(data), "{}", a);
+                }
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn blake3_modes() {
+        let data = data();
+        let key = [9u8; 32];
+        let mut hasher = MultiHasher::new(&[Blake3_256, Blake3_512, Sha1]).with_blake3_key(&key);
+        hasher.update(&data);
+        let digests = hasher.clone().finalize();
+        assert_eq!(digests[0].1, blake3::keyed_hash(&key, &data).as_bytes());
+        assert_eq!(
+            &digests[1].1[..32],
+            blake3::keyed_hash(&key, &data).as_bytes()
+        );
+        assert_eq!(digests[2].1, Sha1.digest(&data));
+        assert_eq!(hasher.finalize(), digests);
+
+        let mut hasher = MultiHasher::new(&[Blake3_256]).with_blake3_context("hashify test");
+        hasher.update(&data);
+        assert_eq!(
+            hasher.finalize()[0].1,
+            blake3::derive_key("hashify test", &data)
+        );
+    }
+
+    #[test]
+    fn macs() {
+        let data = data();
+        let mut hasher = MultiHasher::new(&[Sha2_256, Sha3_256, Md5])
+            .with_hmac_key(b"key")
+            .unwrap();
+        hasher.update(&data);
+        for (a, digest) in hasher.finalize() {
+            let mut mac = a.mac(b"key").unwrap();
+            mac.update(&data);
+            assert_eq!(digest, mac.finalize().into_vec(), "{}", a);
+        }
+        assert!(MultiHasher::new(&[Blake3_256])
+            .with_hmac_key(b"key")
+            .is_err());
+    }
+
+    #[test]
+    fn xof_lengths() {
+        let data = data();
+        let mut hasher = MultiHasher::new(&[Shake256, CShake128, K12, Blake3, Sha1])
+            .with_length(100)
+            .with_customization(b"hashify");
+        hasher.update(&data);
+        let digests = hasher.finalize();
+        for (a, digest) in &digests[..4] {
+            let mut xof = a.xof(100, b"hashify").unwrap();
+            xof.update(&data);
+            assert_eq!(digest, &xof.finalize().into_vec(), "{}", a);
+        }
+        assert_eq!(digests[4].1, Sha1.digest(&data));
+        assert_eq!(&digests[0].1[..64], Shake256.digest(&data));
+    }
+
+    #[test]
+    fn blake2_params() {
+        let data = data();
+        let mut hasher = MultiHasher::new(&[Blake2b512, Blake2s256, Sha1])
+            .with_blake2_key(b"key")
+            .and_then(|h| h.with_blake2_salt(b"salt"))
+            .and_then(|h| h.with_blake2_personal(b"hashify"))
+            .unwrap();
+        hasher.update(&data);
+        let digests = hasher.finalize();
+        let mut mac =
+            blake2::Blake2bMac512::new_with_salt_and_personal(b"key", b"salt", b"hashify").unwrap();
+        hmac::Mac::update(&mut mac, &data);
+        assert_eq!(digests[0].1, hmac::Mac::finalize(mac).into_bytes().to_vec());
+        let mut mac =
+            blake2::Blake2sMac256::new_with_salt_and_personal(b"key", b"salt", b"hashify").unwrap();
+        hmac::Mac::update(&mut mac, &data);
+        assert_eq!(digests[1].1, hmac::Mac::finalize(mac).into_bytes().to_vec());
+        assert_eq!(digests[2].1, Sha1.digest(&data));
+        assert!(MultiHasher::new(&[Blake2s])
+            .with_blake2_salt(&[0; 9])
+            .is_err());
+    }
+
+    #[test]
+    fn blake2_lengths() {
+        let data = data();
+        let mut hasher =
+            MultiHasher::sized(&[(Blake2b, 20), (Blake2b, 40), (Blake2s, 20)]).unwrap();
+        hasher.update(&data);
+        for (a, digest) in hasher.finalize() {
+            let mut blake2 = a.blake2(digest.len(), b"", b"", b"").unwrap();
+            blake2.update(&data);
+            assert_eq!(digest, blake2.finalize().into_vec(), "{}", a);
+        }
+        let mut var = blake2::Blake2bVar::new(20).unwrap();
+        Update::update(&mut var, b"abc");
+        let mut out = [0u8; 20];
+        var.finalize_variable(&mut out).unwrap();
+        assert_eq!(
+            Blake2b.blake2(20, b"", b"", b"").map(|mut b| {
+                b.update(b"abc");
+                b.finalize().into_vec()
+            }),
+            Ok(out.to_vec())
+        );
+        assert!(MultiHasher::sized(&[(Blake2s, 40)]).is_err());
+        assert!(Blake2b256.blake2(20, b"", b"", b"").is_err());
+    }
+}
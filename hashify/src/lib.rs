@@ -0,0 +1,72 @@
+#![deny(
+    warnings,
+    unsafe_code,
+    unused_import_braces,
+    unused_qualifications,
+    trivial_casts,
+    trivial_numeric_casts
+)]
+
+use std::fmt::Display;
+
+mod algorithm;
+pub use algorithm::HashAlgorithm;
+
+mod blake2t;
+
+mod blake3t;
+
+mod hasher;
+pub use hasher::{Digests, MultiHasher};
+
+mod mac;
+
+mod manifest;
+pub use manifest::{Entry, ManifestFormat};
+
+mod tree;
+pub use tree::{tree_digest, Symlinks, Tree, TreeFile};
+
+mod verifier;
+pub use verifier::{Trial, Verifier};
+
+mod xof;
+
+/// The order of the digest bytes in a checksum. Most tools write big-endian.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ByteOrder {
+    Big,
+    Little,
+}
+
+impl ByteOrder {
+    pub fn parse<I: AsRef<str>>(s: I) -> Result<Self, String> {
+        match s.as_ref() {
+            "big" => Ok(ByteOrder::Big),
+            "little" => Ok(ByteOrder::Little),
+            s => Err(format!("Unrecognized byteorder - \"{}\"", s)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ByteOrder::Big => "big",
+            ByteOrder::Little => "little",
+        }
+    }
+
+    /// The digest in this byte order
+    pub fn apply(&self, digest: &[u8]) -> Vec<u8> {
+        let mut bytes = digest.to_vec();
+        if *self == ByteOrder::Little {
+            bytes.reverse();
+        }
+        bytes
+    }
+}
+
+impl Display for ByteOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -0,0 +1,242 @@
+use crate::hasher::SyncDigest;
+use crate::HashAlgorithm::{self, *};
+use digest::{core_api::CoreWrapper, DynDigest, ExtendableOutput, InvalidBufferSize, Update};
+use hmac::{Mac, SimpleHmac};
+use sha3::{CShake128, CShake128Core, CShake256, CShake256Core};
+
+/// HMAC for the Merkle–Damgård hashes and Whirlpool, KMAC for SHA-3 and
+/// KMACXOF for SHAKE and cSHAKE. `len` and `customization` only change the
+/// KMACXOFs.
+pub(crate) fn mac(
+    algorithm: HashAlgorithm,
+    key: &[u8],
+    len: usize,
+    customization: &[u8],
+) -> Result<Box<dyn SyncDigest>, String> {
+    fn hmac<D>(key: &[u8]) -> Box<dyn SyncDigest>
+    where
+        SimpleHmac<D>: SyncDigest,
+        D: digest::Digest + digest::core_api::BlockSizeUser + 'static,
+    {
+        // HMAC accepts keys of any length
+        Box::new(<SimpleHmac<D> as Mac>::new_from_slice(key).unwrap())
+    }
+    match algorithm {
+        Md5 => Ok(hmac::<md5::Md5>(key)),
+        Sha1 => Ok(hmac::<sha1::Sha1>(key)),
+        Sha2_224 => Ok(hmac::<sha2::Sha224>(key)),
+        Sha2_256 => Ok(hmac::<sha2::Sha256>(key)),
+        Sha2_384 => Ok(hmac::<sha2::Sha384>(key)),
+        Sha2_512 => Ok(hmac::<sha2::Sha512>(key)),
+        Sha2_512T224 => Ok(hmac::<sha2::Sha512_224>(key)),
+        Sha2_512T256 => Ok(hmac::<sha2::Sha512_256>(key)),
+        Ripemd128 => Ok(hmac::<ripemd::Ripemd128>(key)),
+        Ripemd160 => Ok(hmac::<ripemd::Ripemd160>(key)),
+        Ripemd320 => Ok(hmac::<ripemd::Ripemd320>(key)),
+        Whirlpool => Ok(hmac::<whirlpool::Whirlpool>(key)),
+        Sha3_224 | Sha3_256 => Ok(Box::new(Kmac::new_128(key, b"", algorithm.output_size()))),
+        Sha3_384 | Sha3_512 => Ok(Box::new(Kmac::new_256(key, b"", algorithm.output_size()))),
+        Shake128 => Ok(Box::new(Kmac::new_128(key, b"", len).into_xof())),
+        Shake256 => Ok(Box::new(Kmac::new_256(key, b"", len).into_xof())),
+        CShake128 => Ok(Box::new(Kmac::new_128(key, customization, len).into_xof())),
+        CShake256 => Ok(Box::new(Kmac::new_256(key, customization, len).into_xof())),
+        a => Err(format!(
+            "{} has no HMAC, BLAKE3 and BLAKE2 have keyed modes",
+            a
+        )),
+    }
+}
+
+#[derive(Clone)]
+enum Shake {
+    S128(CShake128),
+    S256(CShake256),
+}
+
+/// KMAC128 or KMAC256 from NIST SP 800-185 with a fixed output length
+#[derive(Clone)]
+pub(crate) struct Kmac {
+    initial: Shake,
+    state: Shake,
+    len: usize,
+    /// KMACXOF leaves the length out so longer outputs extend shorter ones
+    xof: bool,
+}
+
+impl Kmac {
+    pub(crate) fn new_128(key: &[u8], customization: &[u8], len: usize) -> Self {
+        let mut shake = CoreWrapper::from_core(CShake128Core::new_with_function_name(
+            b"KMAC",
+            customization,
+        ));
+        shake.update(&bytepad(&encode_string(key), 168));
+        Kmac::new(Shake::S128(shake), len)
+    }
+
+    pub(crate) fn new_256(key: &[u8], customization: &[u8], len: usize) -> Self {
+        let mut shake = CoreWrapper::from_core(CShake256Core::new_with_function_name(
+            b"KMAC",
+            customization,
+        ));
+        shake.update(&bytepad(&encode_string(key), 136));
+        Kmac::new(Shake::S256(shake), len)
+    }
+
+    fn new(shake: Shake, len: usize) -> Self {
+        Kmac {
+            initial: shake.clone(),
+            state: shake,
+            len,
+            xof: false,
+        }
+    }
+
+    pub(crate) fn into_xof(self) -> Self {
+        Kmac { xof: true, ..self }
+    }
+
+    fn finish(&self, state: Shake, out: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        if out.len() != self.len {
+            return Err(InvalidBufferSize);
+        }
+        let suffix = right_encode(if self.xof { 0 } else { self.len as u64 * 8 });
+        match state {
+            Shake::S128(mut s) => {
+                s.update(&suffix);
+                s.finalize_xof_into(out);
+            }
+            Shake::S256(mut s) => {
+                s.update(&suffix);
+                s.finalize_xof_into(out);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DynDigest for Kmac {
+    fn update(&mut self, data: &[u8]) {
+        match &mut self.state {
+            Shake::S128(s) => s.update(data),
+            Shake::S256(s) => s.update(data),
+        }
+    }
+
+    fn finalize_into(self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        let state = self.state.clone();
+        self.finish(state, buf)
+    }
+
+    fn finalize_into_reset(&mut self, out: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        let state = std::mem::replace(&mut self.state, self.initial.clone());
+        self.finish(state, out)
+    }
+
+    fn reset(&mut self) {
+        self.state = self.initial.clone();
+    }
+
+    fn output_size(&self) -> usize {
+        self.len
+    }
+
+    fn box_clone(&self) -> Box<dyn DynDigest> {
+        Box::new(self.clone())
+    }
+}
+
+fn left_encode(x: u64) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count().min(7);
+    let mut out = vec![(8 - skip) as u8];
+    out.extend_from_slice(&bytes[skip..]);
+    out
+}
+
+fn right_encode(x: u64) -> Vec<u8> {
+    let mut out = left_encode(x);
+    out.rotate_left(1);
+    out
+}
+
+fn encode_string(s: &[u8]) -> Vec<u8> {
+    let mut out = left_encode(s.len() as u64 * 8);
+    out.extend_from_slice(s);
+    out
+}
+
+fn bytepad(x: &[u8], w: usize) -> Vec<u8> {
+    let mut out = left_encode(w as u64);
+    out.extend_from_slice(x);
+    out.resize(out.len().div_ceil(w) * w, 0);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serious::Encoding;
+
+    fn hex(s: &str) -> Vec<u8> {
+        Encoding::decode_bytes(s.replace(' ', "").as_bytes(), Encoding::LowHex).unwrap()
+    }
+
+    #[test]
+    fn kmac() {
+        // NIST SP 800-185 samples
+        let key = (0x40..0x60).collect::<Vec<u8>>();
+        let data = [0u8, 1, 2, 3];
+        let mut kmac = Kmac::new_128(&key, b"", 32);
+        DynDigest::update(&mut kmac, &data);
+        assert_eq!(
+            kmac.box_clone().finalize().into_vec(),
+            hex("e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e")
+        );
+        let mut out = [0u8; 32];
+        kmac.finalize_into_reset(&mut out).unwrap();
+        DynDigest::update(&mut kmac, &data);
+        assert_eq!(kmac.finalize_reset().into_vec(), out);
+
+        let mut kmac = Kmac::new_128(&key, b"My Tagged Application", 32);
+        DynDigest::update(&mut kmac, &data);
+        assert_eq!(
+            Box::new(kmac).finalize().into_vec(),
+            hex("3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5")
+        );
+
+        let mut kmac = Kmac::new_256(&key, b"My Tagged Application", 64);
+        DynDigest::update(&mut kmac, &data);
+        assert_eq!(
+            Box::new(kmac).finalize().into_vec(),
+            hex(
+                "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7\
+                 f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd"
+            )
+        );
+
+        // KMACXOF128 sample 4
+        let mut kmac = Kmac::new_128(&key, b"", 32).into_xof();
+        DynDigest::update(&mut kmac, &data);
+        assert_eq!(
+            Box::new(kmac).finalize().into_vec(),
+            hex("cd83740bbd92ccc8cf032b1481a0f4460e7ca9dd12b08a0c4031178bacd6ec35")
+        );
+    }
+
+    #[test]
+    fn hmac() {
+        // RFC 4231 test case 2
+        let mut mac = mac(Sha2_256, b"Jefe", 32, b"").unwrap();
+        mac.update(b"what do ya want for nothing?");
+        assert_eq!(
+            mac.finalize().into_vec(),
+            hex("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+        );
+        for a in HashAlgorithm::values() {
+            match a.is_blake3() || a.name().starts_with("blake2") || a == K12 {
+                true => assert!(a.mac(b"k").is_err()),
+                false => assert_eq!(a.mac(b"k").unwrap().output_size(), a.output_size()),
+            }
+        }
+    }
+}
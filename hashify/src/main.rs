@@ -9,63 +9,58 @@
 
 use clap::{App, Arg, ArgMatches, SubCommand};
 use colored::*;
+use hashify::{
+    tree_digest, ByteOrder, Digests, Entry, HashAlgorithm, ManifestFormat, MultiHasher, Symlinks,
+    Tree, Verifier,
+};
+use rayon::prelude::*;
 use serious::Encoding as Code;
-use sha1::Sha1;
-use sha2::Digest;
-use std::collections::HashMap;
+use serious::{Charset, Pipeline};
 use std::fs::File;
-use std::io::{self, Read, Write};
-use std::path::PathBuf;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 use stringreader::StringReader;
 
-#[cfg(target_pointer_width = "64")]
-use blake2::Blake2b as Blake2_512;
-#[cfg(target_pointer_width = "32")]
-use blake2::Blake2s as Blake2_512;
-use digest::typenum::U64;
-use digest::FixedOutputReset;
-
-mod blake2t;
-use blake2t::{Blake2_256, Blake2_384};
-
-mod blake3t;
-use blake3t::{Blake3_256, Blake3_384, Blake3_512};
-
-mod constants;
-use constants::*;
-
 fn main() {
-    let valid_hashes = [
-        SHA3_224,
-        SHA3_256,
-        SHA3_384,
-        SHA3_512,
-        SHA2_224,
-        SHA2_256,
-        SHA2_384,
-        SHA2_512,
-        SHA2_512_T224,
-        SHA2_512_T256,
-        BLAKE2_256,
-        BLAKE2_384,
-        BLAKE2_512,
-        BLAKE3_256,
-        BLAKE3_384,
-        BLAKE3_512,
-        WHIRLPOOL,
-        SHA1,
-        RIPEMD320,
-        RIPEMD160,
-        RIPEMD128,
-        MD5,
-    ];
-    let encodings = serious::Encoding::values()
+    let hashes = HashAlgorithm::values()
+        .iter()
+        .map(|a| a.name())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    let verify_type_help = format!(
+        "The specific hash to use to compute the checksum. [possible values: {}]",
+        hashes
+    );
+    let create_type_help = format!(
+        "The specific hash to use to compute the checksum [possible values: {}]",
+        hashes
+    );
+    let encodings = Code::values()
         .iter()
-        .map(|e| e.to_string())
+        .map(|e| e.name())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    let verify_enc_help = format!(
+        "The checksum encoding can only specify one. [possible values: {}]",
+        encodings
+    );
+    let create_enc_help = format!("The output encoding. [possible values: {}]", encodings);
+    let charsets = Charset::values()
+        .iter()
+        .map(|c| c.to_string())
         .collect::<Vec<String>>();
-    let encs = encodings.iter().map(|e| e.as_str()).collect::<Vec<&str>>();
+    let charsets = charsets.iter().map(|c| c.as_str()).collect::<Vec<&str>>();
     let default_enc = Code::LowHex.to_string();
-    let create_default_type = ["sha3-256", "sha2-256", "sha2-512-t256", "blake2-256"].join(",");
+    let create_default_type = [
+        HashAlgorithm::Sha3_256,
+        HashAlgorithm::Sha2_256,
+        HashAlgorithm::Sha2_512T256,
+        HashAlgorithm::Blake2b256,
+    ]
+    .iter()
+    .map(|a| a.name())
+    .collect::<Vec<&str>>()
+    .join(",");
     let matches = App::new("Hashify")
         .version("0.1")
         .author("Michael Lodder")
@@ -76,9 +71,9 @@ fn main() {
                  .short("t")
                  .long("type")
                  .value_name("VERIFY_HASH_TYPE")
-                 .help("The specific hash to use to compute the checksum.")
+                 .help(&verify_type_help)
                  .takes_value(true)
-                 .possible_values(&valid_hashes)
+                 .validator(valid_hash)
                  .value_delimiter(",")
                  .allow_hyphen_values(true)
                  .required(false))
@@ -86,9 +81,9 @@ fn main() {
                 .short("e")
                 .long("encoding")
                 .value_name("VERIFY_ENCODING")
-                .help("The checksum encoding can only specify one.")
+                .help(&verify_enc_help)
                 .takes_value(true)
-                .possible_values(encs.as_slice())
+                .validator(valid_encoding)
                 .max_values(1)
                 .required(false))
             .arg(Arg::with_name("byteorder")
@@ -106,6 +101,87 @@ fn main() {
                 .help("Show all tried algorithms in output.")
                 .takes_value(false)
                 .required(false))
+            .arg(Arg::with_name("key")
+                .long("key")
+                .value_name("KEY")
+                .help("Compute a BLAKE3 or BLAKE2 keyed hash (MAC) with a key given as hex or a file, 32 bytes for BLAKE3 and up to 64 for BLAKE2b or 32 for BLAKE2s. Use '-' to type it at a prompt or read it from STDIN.")
+                .takes_value(true)
+                .conflicts_with("derive-key")
+                .required(false))
+            .arg(Arg::with_name("derive-key")
+                .long("derive-key")
+                .value_name("CONTEXT")
+                .help("Derive a BLAKE3 key from the input for this context string, like \"example.com 2026-01-01 session tokens\".")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("hmac-key")
+                .long("hmac-key")
+                .value_name("KEY")
+                .help("Compute an HMAC, or KMAC for SHA-3, with this key given as text or a file. Use '-' to type it at a prompt or read it from STDIN.")
+                .takes_value(true)
+                .conflicts_with_all(&["key", "derive-key"])
+                .required(false))
+            .arg(Arg::with_name("key-encoding")
+                .long("key-encoding")
+                .value_name("ENCODING")
+                .help("Decode the --key or --hmac-key with this encoding. --hmac-key defaults to raw bytes and --key to hex, use blob for raw bytes.")
+                .takes_value(true)
+                .validator(valid_encoding)
+                .required(false))
+            .arg(Arg::with_name("salt")
+                .long("salt")
+                .value_name("SALT")
+                .help("A BLAKE2 salt up to 16 bytes for blake2b or 8 for blake2s.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("personal")
+                .long("personal")
+                .value_name("TEXT")
+                .help("A BLAKE2 personalization up to 16 bytes for blake2b or 8 for blake2s, so the same input hashes differently for each application.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("customization")
+                .long("customization")
+                .value_name("TEXT")
+                .help("A customization string for cshake128, cshake256 and k12 so the same input hashes differently for each application.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("exclude")
+                .long("exclude")
+                .value_name("PATTERN")
+                .help("Leave out paths in a directory matching a gitignore style pattern like \"target/\" or \"*.log\". Can be given more than once.")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .required(false))
+            .arg(Arg::with_name("symlinks")
+                .long("symlinks")
+                .value_name("POLICY")
+                .help("What to do with symbolic links in a directory. follow hashes what they point to, skip leaves them out and link hashes the link's target path.")
+                .takes_value(true)
+                .possible_values(&["follow", "skip", "link"])
+                .default_value("follow")
+                .required(false))
+            .arg(Arg::with_name("pipeline")
+                .short("p")
+                .long("pipeline")
+                .value_name("PIPELINE")
+                .help("Transform the input with a serious pipeline like \"base64 | gunzip\" before hashing.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("text-encoding")
+                .long("text-encoding")
+                .value_name("CHARSET")
+                .help("Treat the input as text and hash it in this character set. The input character set is detected from its byte order mark and defaults to UTF-8.")
+                .takes_value(true)
+                .possible_values(charsets.as_slice())
+                .required(false))
+            .arg(Arg::with_name("bom")
+                .long("bom")
+                .help("Include a byte order mark in the text that is hashed.")
+                .takes_value(false)
+                .requires("text-encoding")
+                .required(false))
             .arg(Arg::with_name("CHECKSUM")
                 .help("The checksum file or text to compare.")
                 .required(true)
@@ -120,9 +196,9 @@ fn main() {
                  .short("t")
                  .long("type")
                  .value_name("OUT_HASH_TYPE")
-                 .help("The specific hash to use to compute the checksum")
+                 .help(&create_type_help)
                  .takes_value(true)
-                 .possible_values(&valid_hashes)
+                 .validator(valid_hash)
                  .value_delimiter(",")
                  .allow_hyphen_values(true)
                  .default_value(&create_default_type)
@@ -131,9 +207,9 @@ fn main() {
                 .short("e")
                 .long("encoding")
                 .value_name("OUT_ENCODING")
-                .help("The output encoding.")
+                .help(&create_enc_help)
                 .takes_value(true)
-                .possible_values(encs.as_slice())
+                .validator(valid_encoding)
                 .value_delimiter(",")
                 .default_value(&default_enc)
                 .required(false))
@@ -147,27 +223,387 @@ fn main() {
                 .value_delimiter(",")
                 .default_value("big")
                 .required(false))
+            .arg(Arg::with_name("key")
+                .long("key")
+                .value_name("KEY")
+                .help("Compute a BLAKE3 or BLAKE2 keyed hash (MAC) with a key given as hex or a file, 32 bytes for BLAKE3 and up to 64 for BLAKE2b or 32 for BLAKE2s. Use '-' to type it at a prompt or read it from STDIN.")
+                .takes_value(true)
+                .conflicts_with("derive-key")
+                .required(false))
+            .arg(Arg::with_name("derive-key")
+                .long("derive-key")
+                .value_name("CONTEXT")
+                .help("Derive a BLAKE3 key from the input for this context string, like \"example.com 2026-01-01 session tokens\".")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("hmac-key")
+                .long("hmac-key")
+                .value_name("KEY")
+                .help("Compute an HMAC, or KMAC for SHA-3, with this key given as text or a file. Use '-' to type it at a prompt or read it from STDIN.")
+                .takes_value(true)
+                .conflicts_with_all(&["key", "derive-key"])
+                .required(false))
+            .arg(Arg::with_name("key-encoding")
+                .long("key-encoding")
+                .value_name("ENCODING")
+                .help("Decode the --key or --hmac-key with this encoding. --hmac-key defaults to raw bytes and --key to hex, use blob for raw bytes.")
+                .takes_value(true)
+                .validator(valid_encoding)
+                .required(false))
+            .arg(Arg::with_name("length")
+                .long("length")
+                .value_name("BYTES")
+                .help("The digest length in bytes for shake128, shake256, cshake128, cshake256, k12, blake3, blake2b and blake2s.")
+                .takes_value(true)
+                .validator(valid_length)
+                .required(false))
+            .arg(Arg::with_name("salt")
+                .long("salt")
+                .value_name("SALT")
+                .help("A BLAKE2 salt up to 16 bytes for blake2b or 8 for blake2s.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("personal")
+                .long("personal")
+                .value_name("TEXT")
+                .help("A BLAKE2 personalization up to 16 bytes for blake2b or 8 for blake2s, so the same input hashes differently for each application.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("customization")
+                .long("customization")
+                .value_name("TEXT")
+                .help("A customization string for cshake128, cshake256 and k12 so the same input hashes differently for each application.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("exclude")
+                .long("exclude")
+                .value_name("PATTERN")
+                .help("Leave out paths in a directory matching a gitignore style pattern like \"target/\" or \"*.log\". Can be given more than once.")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .required(false))
+            .arg(Arg::with_name("symlinks")
+                .long("symlinks")
+                .value_name("POLICY")
+                .help("What to do with symbolic links in a directory. follow hashes what they point to, skip leaves them out and link hashes the link's target path.")
+                .takes_value(true)
+                .possible_values(&["follow", "skip", "link"])
+                .default_value("follow")
+                .required(false))
+            .arg(Arg::with_name("pipeline")
+                .short("p")
+                .long("pipeline")
+                .value_name("PIPELINE")
+                .help("Transform the input with a serious pipeline like \"base64 | gunzip\" before hashing.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("text-encoding")
+                .long("text-encoding")
+                .value_name("CHARSET")
+                .help("Treat the input as text and hash it in this character set. The input character set is detected from its byte order mark and defaults to UTF-8.")
+                .takes_value(true)
+                .possible_values(charsets.as_slice())
+                .required(false))
+            .arg(Arg::with_name("bom")
+                .long("bom")
+                .help("Include a byte order mark in the text that is hashed.")
+                .takes_value(false)
+                .requires("text-encoding")
+                .required(false))
+            .arg(Arg::with_name("recursive")
+                .short("r")
+                .long("recursive")
+                .help("List the digest of every file in directories as well as the directory digest.")
+                .takes_value(false)
+                .required(false))
+            .arg(Arg::with_name("jobs")
+                .short("j")
+                .long("jobs")
+                .value_name("N")
+                .help("Hash up to N files at once. Defaults to the number of cores.")
+                .takes_value(true)
+                .validator(valid_jobs)
+                .required(false))
+            .arg(Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .value_name("FORMAT")
+                .help("The output format. table is for reading, gnu writes sha256sum compatible lines, bsd writes tagged lines like SHA256 (file) = digest, json and csv write a manifest with each file's path, size, algorithm and digest.")
+                .takes_value(true)
+                .possible_values(&["table", "gnu", "bsd", "json", "csv"])
+                .default_value("table")
+                .required(false))
             .arg(Arg::with_name("INPUT")
-                 .help("The file or text to process. If no input is specified or input is '-', input is received from STDIN")
+                 .help("The files or text to process. If no input is specified or input is '-', input is received from STDIN")
                  .required(false)
+                 .multiple(true)
                  .index(1))
-         ).get_matches();
+         )
+        .subcommand(SubCommand::with_name("check")
+            .about("Verify every file listed in a checksum manifest like SHA256SUMS, in GNU (sha256sum) or BSD (SHA256 (file) = digest) format. If FILE is '-', the manifest is read from STDIN")
+            .arg(Arg::with_name("quiet")
+                .long("quiet")
+                .help("Don't print OK for each successfully verified file.")
+                .takes_value(false)
+                .required(false))
+            .arg(Arg::with_name("status")
+                .long("status")
+                .help("Don't output anything, the exit code shows success.")
+                .takes_value(false)
+                .required(false))
+            .arg(Arg::with_name("ignore-missing")
+                .long("ignore-missing")
+                .help("Don't fail or report status for missing files.")
+                .takes_value(false)
+                .required(false))
+            .arg(Arg::with_name("strict")
+                .long("strict")
+                .help("Exit non-zero for improperly formatted checksum lines.")
+                .takes_value(false)
+                .required(false))
+            .arg(Arg::with_name("warn")
+                .short("w")
+                .long("warn")
+                .help("Warn about improperly formatted checksum lines.")
+                .takes_value(false)
+                .required(false))
+            .arg(Arg::with_name("jobs")
+                .short("j")
+                .long("jobs")
+                .value_name("N")
+                .help("Hash up to N files at once. Defaults to the number of cores.")
+                .takes_value(true)
+                .validator(valid_jobs)
+                .required(false))
+            .arg(Arg::with_name("FILE")
+                .help("The manifest to read.")
+                .required(true)
+                .index(1))
+        ).get_matches();
+
+    if let Some(jobs) = matches.subcommand().1.and_then(|m| m.value_of("jobs")) {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs.parse().unwrap())
+            .build_global()
+            .unwrap();
+    }
 
     if let Some(matches) = matches.subcommand_matches("create") {
         create(matches);
     } else if let Some(matches) = matches.subcommand_matches("verify") {
         verify(matches);
+    } else if let Some(matches) = matches.subcommand_matches("check") {
+        check(matches);
     } else {
-        quit("Please specify a command to run [create | verify]".to_string());
+        quit("Please specify a command to run [create | verify | check]".to_string());
+    }
+}
+
+fn valid_encoding(e: String) -> Result<(), String> {
+    Code::parse(e).map(|_| ())
+}
+
+fn valid_jobs(j: String) -> Result<(), String> {
+    match j.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(()),
+        _ => Err(format!("Jobs must be a positive number - \"{}\"", j)),
     }
 }
 
+fn valid_length(l: String) -> Result<(), String> {
+    match l.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(()),
+        _ => Err(format!("Length must be a positive number - \"{}\"", l)),
+    }
+}
+
+fn valid_hash(h: String) -> Result<(), String> {
+    HashAlgorithm::parse(h).map(|_| ())
+}
+
+fn hash_types(matches: &ArgMatches) -> Option<Vec<HashAlgorithm>> {
+    matches
+        .values_of("type")
+        .map(|v| v.map(|t| HashAlgorithm::parse(t).unwrap()).collect())
+}
+
 fn create(matches: &ArgMatches) {
-    let hash_types: Vec<&str> = matches.values_of("type").unwrap().collect();
-    let out_hash = get_hashes_from_input(matches, hash_types);
+    let types = hash_types(matches).unwrap();
+    let mut hasher = MultiHasher::new(&types);
+    if let Some(length) = matches.value_of("length").map(|l| l.parse().unwrap()) {
+        let sized = types
+            .iter()
+            .filter(|a| a.lengths().start() < a.lengths().end())
+            .collect::<Vec<_>>();
+        if sized.is_empty() {
+            quit("--length only works with shake128, shake256, cshake128, cshake256, k12, blake3, blake2b and blake2s".to_string());
+        }
+        if let Some(a) = sized.iter().find(|a| !a.lengths().contains(&length)) {
+            quit(format!(
+                "{} digests are at most {} bytes",
+                a,
+                a.lengths().end()
+            ));
+        }
+        hasher = hasher.with_length(length);
+    }
+    let hasher = match input_hasher(matches, hasher) {
+        Ok(hasher) => hasher,
+        Err(e) => {
+            quit(e);
+            return;
+        }
+    };
+    let inputs = match matches.values_of("INPUT") {
+        Some(inputs) => inputs.map(Some).collect(),
+        None => vec![None],
+    };
+    let format = matches.value_of("format").unwrap();
+    if format != "table" {
+        let order = matches.value_of("byteorder").unwrap();
+        let encoding = matches.value_of("encoding").unwrap();
+        let manifest = matches!(format, "gnu" | "bsd");
+        if manifest && (order != "big" || Code::parse(encoding).unwrap() != Code::LowHex) {
+            quit(format!(
+                "The {} format only writes big-endian hex digests",
+                format
+            ));
+        }
+    }
+
+    // Text is only hashed for a single input shown as a table, anything else
+    // must be a file so a typo can't end up in a manifest
+    let path_only = inputs.len() > 1 || format != "table";
+
+    // Each input, or each file and then the directory itself with -r
+    let pipeline = input_pipeline(matches);
+    let items = inputs
+        .par_iter()
+        .map(|input| {
+            let dir = input.filter(|i| Path::new(i).is_dir());
+            match dir.filter(|_| matches.is_present("recursive")) {
+                Some(dir) => {
+                    let dir_path = format!("{}/", dir.trim_end_matches('/'));
+                    match hash_tree(matches, dir, &hasher) {
+                        Ok((files, tree)) => {
+                            let mut items = files
+                                .into_iter()
+                                .map(|(path, res)| (format!("{}{}", dir_path, path), res, false))
+                                .collect::<Vec<_>>();
+                            items.push((dir_path, tree, true));
+                            items
+                        }
+                        Err(e) => vec![(dir_path, Err(e), true)],
+                    }
+                }
+                None => {
+                    let res = get_hashes_from_input(
+                        matches,
+                        *input,
+                        &hasher,
+                        pipeline.as_ref(),
+                        path_only,
+                    );
+                    vec![(input.unwrap_or("-").to_string(), res, dir.is_some())]
+                }
+            }
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+
+    let mut failed = false;
+    let mut rows = Vec::new();
+    let mut printed = 0;
+    for (path, res, is_dir) in &items {
+        let (size, hashes) = match res {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("hashify: {}: {}", path, e);
+                failed = true;
+                continue;
+            }
+        };
+        if format == "table" {
+            if items.len() > 1 {
+                if printed > 0 {
+                    println!();
+                }
+                println!("{}:", path);
+            }
+            print_table(matches, hashes.clone());
+            printed += 1;
+        } else if !(*is_dir && matches!(format, "gnu" | "bsd")) {
+            // sha256sum can't check a directory digest so manifests leave it out
+            for (alg, hash) in hashes {
+                rows.push((path.as_str(), *size, *alg, hash.clone()));
+            }
+        }
+    }
+
+    match format {
+        "gnu" | "bsd" => {
+            let format = match format {
+                "gnu" => ManifestFormat::Gnu,
+                _ => ManifestFormat::Bsd,
+            };
+            for (path, _, alg, hash) in rows {
+                println!("{}", Entry::new(path, alg, hash, format).line());
+            }
+        }
+        "json" | "csv" => {
+            let order = ByteOrder::parse(matches.value_of("byteorder").unwrap()).unwrap();
+            let encoding = Code::parse(matches.value_of("encoding").unwrap()).unwrap();
+            let mut lines = Vec::new();
+            for (path, size, alg, hash) in rows {
+                let digest = match Code::encode(order.apply(&hash), encoding).try_into_string() {
+                    Ok(d) => d,
+                    Err(e) => {
+                        quit(format!("Unable to encode {} as {} - {}", alg, encoding, e));
+                        String::new()
+                    }
+                };
+                lines.push(match format {
+                    "json" => format!(
+                        "  {{\"path\": \"{}\", \"size\": {}, \"algorithm\": \"{}\", \"digest\": \"{}\"}}",
+                        Code::encode(path, Code::Json).into_string(),
+                        size,
+                        alg,
+                        Code::encode(digest, Code::Json).into_string()
+                    ),
+                    _ => format!("{},{},{},{}", csv_field(path), size, alg, csv_field(&digest)),
+                });
+            }
+            match format {
+                "json" if lines.is_empty() => println!("[]"),
+                "json" => println!("[\n{}\n]", lines.join(",\n")),
+                _ => {
+                    println!("path,size,algorithm,digest");
+                    for line in lines {
+                        println!("{}", line);
+                    }
+                }
+            }
+        }
+        _ => (),
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Quote a CSV field when it has a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+fn print_table(matches: &ArgMatches, out_hash: Digests) {
     let label_width = out_hash
         .iter()
-        .fold(0usize, |a, (label, _)| std::cmp::max(a, label.len()));
+        .fold(0usize, |a, (alg, _)| std::cmp::max(a, alg.name().len()));
     let byte_width = matches
         .values_of("byteorder")
         .unwrap()
@@ -177,18 +613,13 @@ fn create(matches: &ArgMatches) {
         .unwrap()
         .fold(0usize, |a, s| std::cmp::max(a, s.len()));
 
-    for (label, hash) in out_hash {
-        let l = name_color(&label);
+    for (alg, hash) in out_hash {
+        let l = name_color(alg);
         for bo in matches.values_of("byteorder").unwrap() {
-            let bytes: Vec<u8> = match bo {
-                "big" => hash.to_vec(),
-                "little" => {
-                    let mut temp = hash.to_vec();
-                    temp.reverse();
-                    temp
-                }
-                _ => {
-                    quit(format!("Unrecognized byteorder - \"{}\"", bo));
+            let bytes = match ByteOrder::parse(bo) {
+                Ok(order) => order.apply(&hash),
+                Err(e) => {
+                    quit(e);
                     Vec::new()
                 }
             };
@@ -214,12 +645,19 @@ fn create(matches: &ArgMatches) {
                         io::stdout().flush().unwrap();
                     }
                     e => {
+                        let text = match Code::encode(bytes.as_slice(), e).try_into_string() {
+                            Ok(t) => t,
+                            Err(err) => {
+                                quit(format!("Unable to encode {} as {} - {}", l, e, err));
+                                String::new()
+                            }
+                        };
                         println!(
                             "{:label_width$} {:byte_width$}-endian {:enc_width$} - {}",
                             l,
                             bo,
                             e.to_string(),
-                            Code::encode(bytes.as_slice(), e).into_string(),
+                            text,
                             label_width = label_width,
                             byte_width = byte_width,
                             enc_width = enc_width
@@ -249,114 +687,89 @@ fn verify(matches: &ArgMatches) {
         None => checksum = checksum_text.as_bytes().to_vec(),
     };
 
-    let mut checksum_str = String::new();
-    if let Ok(s) = String::from_utf8(checksum.to_vec()).map_err(|e| format!("{}", e)) {
-        checksum_str = s.to_string();
-    } else {
-        quit("Checksum cannot be read properly".to_string());
-    }
-
-    let encoding_checksums = get_encoding_checksums(matches, &checksum_str, &checksum);
-
-    let mut hash_types = Vec::new();
-
-    if let Some(ht) = matches.values_of("type") {
-        hash_types = ht.collect();
+    let encoding = matches
+        .value_of("encoding")
+        .map(|e| Code::parse(e).unwrap());
+    let mut verifier = match Verifier::new(&checksum, encoding) {
+        Ok(v) => v,
+        Err(e) => {
+            quit(e);
+            return;
+        }
+    };
+    if let Some(types) = hash_types(matches) {
+        verifier = verifier.with_algorithms(&types);
     } else {
-        for cksum in encoding_checksums.values() {
-            match cksum.len() {
-                16 => {
-                    hash_types.push(RIPEMD128);
-                    hash_types.push(MD5);
-                }
-                20 => {
-                    hash_types.push(SHA1);
-                    hash_types.push(RIPEMD160);
-                }
-                28 => {
-                    hash_types.push(SHA2_224);
-                    hash_types.push(SHA2_512_T224);
-                    hash_types.push(SHA3_224);
-                }
-                32 => {
-                    hash_types.push(SHA2_256);
-                    hash_types.push(SHA2_512_T256);
-                    hash_types.push(BLAKE2_256);
-                    hash_types.push(SHA3_256);
-                    hash_types.push(BLAKE3_256);
-                }
-                40 => {
-                    hash_types.push(RIPEMD320);
-                }
-                48 => {
-                    hash_types.push(SHA2_384);
-                    hash_types.push(SHA3_384);
-                    hash_types.push(BLAKE2_384);
-                    hash_types.push(BLAKE3_384);
-                }
-                64 => {
-                    hash_types.push(SHA2_512);
-                    hash_types.push(SHA3_512);
-                    hash_types.push(BLAKE2_512);
-                    hash_types.push(BLAKE3_512);
-                    hash_types.push(WHIRLPOOL);
+        // Only the algorithms the keys and parameters work with
+        let blake2 = matches.is_present("salt") || matches.is_present("personal");
+        let keyed = verifier
+            .algorithms()
+            .iter()
+            .copied()
+            .filter(|a| {
+                if matches.is_present("hmac-key") {
+                    a.mac(b"").is_ok()
+                } else if matches.is_present("derive-key") {
+                    a.is_blake3()
+                } else if blake2 {
+                    a.is_blake2()
+                } else if matches.is_present("key") {
+                    a.is_blake2() || a.is_blake3()
+                } else {
+                    true
                 }
-                _ => (),
-            }
-        }
+            })
+            .collect::<Vec<_>>();
+        verifier = verifier.with_algorithms(&keyed);
+    }
+    if let Some(bo) = matches.value_of("byteorder") {
+        verifier = verifier.with_byte_order(ByteOrder::parse(bo).unwrap());
     }
 
-    if hash_types.is_empty() {
+    if verifier.algorithms().is_empty() {
         quit("Unknown checksum length".to_string());
     }
 
-    let mut big_endian = true;
-    let mut little_endian = true;
-    if let Some(bo) = matches.value_of("byteorder") {
-        big_endian = bo == "big";
-        little_endian = bo == "little";
-    }
-
-    let hashes = get_hashes_from_input(matches, hash_types);
-
-    let mut trials = Vec::new();
-    let mut name_width = 0;
-    let mut enc_width = 0;
-    let mut byte_width = 0;
-    for (name, output) in &hashes {
-        for (encoding, cksum) in &encoding_checksums {
-            if big_endian {
-                let l = name_color(name);
-                if output == cksum {
-                    name_width = std::cmp::max(name_width, name.len());
-                    enc_width = std::cmp::max(enc_width, encoding.len());
-                    byte_width = std::cmp::max(byte_width, 10);
-                    trials.insert(0, (l, "big-endian", encoding, "pass".green()));
-                } else {
-                    name_width = std::cmp::max(name_width, name.len());
-                    enc_width = std::cmp::max(enc_width, encoding.len());
-                    byte_width = std::cmp::max(byte_width, 10);
-                    trials.push((l, "big-endian", encoding, "fail".red()));
-                }
-            }
-            if little_endian {
-                let l = name_color(name);
-                let mut temp = output.to_vec();
-                temp.reverse();
-                if temp == *cksum {
-                    name_width = std::cmp::max(name_width, name.len());
-                    enc_width = std::cmp::max(enc_width, encoding.len());
-                    byte_width = std::cmp::max(byte_width, 13);
-                    trials.insert(0, (l, "little-endian", encoding, "pass".green()));
-                } else {
-                    name_width = std::cmp::max(name_width, name.len());
-                    enc_width = std::cmp::max(enc_width, encoding.len());
-                    byte_width = std::cmp::max(byte_width, 13);
-                    trials.push((l, "little-endian", encoding, "fail".red()));
-                }
-            }
+    let hasher = match input_hasher(matches, verifier.hasher()) {
+        Ok(hasher) => hasher,
+        Err(e) => {
+            quit(e);
+            return;
         }
-    }
+    };
+    let pipeline = input_pipeline(matches);
+    let hashes = match get_hashes_from_input(
+        matches,
+        matches.value_of("INPUT"),
+        &hasher,
+        pipeline.as_ref(),
+        false,
+    ) {
+        Ok((_, hashes)) => hashes,
+        Err(e) => {
+            quit(e);
+            return;
+        }
+    };
+    let trials = verifier.verify(&hashes);
+    let passed = trials.iter().any(|t| t.passed);
+    let trials = trials
+        .into_iter()
+        .map(|t| {
+            (
+                name_color(t.algorithm),
+                format!("{}-endian", t.byte_order),
+                t.encoding.to_string(),
+                match t.passed {
+                    true => "pass".green(),
+                    false => "fail".red(),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    let name_width = trials.iter().map(|t| t.0.len()).max().unwrap_or(0);
+    let byte_width = trials.iter().map(|t| t.1.len()).max().unwrap_or(0);
+    let enc_width = trials.iter().map(|t| t.2.len()).max().unwrap_or(0);
 
     match matches.occurrences_of("verbose") {
         1 => {
@@ -384,137 +797,402 @@ fn verify(matches: &ArgMatches) {
             enc_width = trials[0].2.len()
         ),
     };
+    if !passed {
+        std::process::exit(1);
+    }
+}
+
+fn check(matches: &ArgMatches) {
+    let name = matches.value_of("FILE").unwrap();
+    let mut manifest = String::new();
+    let res = match name {
+        "-" => io::stdin().read_to_string(&mut manifest),
+        _ => File::open(name).and_then(|mut f| f.read_to_string(&mut manifest)),
+    };
+    if let Err(e) = res {
+        eprintln!("hashify: {}: {}", name, e);
+        std::process::exit(1);
+    }
+
+    let status = matches.is_present("status");
+    let quiet = matches.is_present("quiet") || status;
+    let ignore_missing = matches.is_present("ignore-missing");
+    let mut improper = 0usize;
+    let mut mismatched = 0usize;
+    let mut unreadable = 0usize;
+    let mut verified = 0usize;
+    let mut formatted = 0usize;
+
+    let lines = manifest
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| Entry::parse(line).map(|entry| (i, entry)))
+        .collect::<Vec<_>>();
+    // None for missing files that are ignored
+    let results = lines
+        .par_iter()
+        .map(|(_, entry)| {
+            let entry = entry.as_ref().ok()?;
+            match File::open(&entry.path) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound && ignore_missing => None,
+                Err(e) => Some(Err(e)),
+                Ok(mut f) => Some(entry.verify_reader(&mut f)),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for ((i, entry), res) in lines.into_iter().zip(results) {
+        let entry = match entry {
+            Ok(entry) => {
+                formatted += 1;
+                entry
+            }
+            Err(e) => {
+                improper += 1;
+                if matches.is_present("warn") && !status {
+                    eprintln!("hashify: {}: {}: {}", name, i + 1, e);
+                }
+                continue;
+            }
+        };
+        let res = match res {
+            Some(res) => res,
+            None => continue,
+        };
+        verified += 1;
+        match res {
+            Ok(true) if !quiet => println!("{}: {}", entry.path, "OK".green()),
+            Ok(true) => (),
+            Ok(false) => {
+                mismatched += 1;
+                if !status {
+                    println!("{}: {}", entry.path, "FAILED".red());
+                }
+            }
+            Err(e) => {
+                unreadable += 1;
+                if !status {
+                    eprintln!("hashify: {}: {}", entry.path, e);
+                    println!("{}: {}", entry.path, "FAILED open or read".red());
+                }
+            }
+        }
+    }
+
+    let plural = |n: usize, one: &str, many: &str| match n {
+        1 => format!("1 {}", one),
+        n => format!("{} {}", n, many),
+    };
+    if formatted == 0 {
+        if !status {
+            eprintln!(
+                "hashify: {}: no properly formatted checksum lines found",
+                name
+            );
+        }
+        std::process::exit(1);
+    }
+    if !status {
+        if improper > 0 {
+            eprintln!(
+                "hashify: WARNING: {} improperly formatted",
+                plural(improper, "line is", "lines are")
+            );
+        }
+        if unreadable > 0 {
+            eprintln!(
+                "hashify: WARNING: {} could not be read",
+                plural(unreadable, "listed file", "listed files")
+            );
+        }
+        if mismatched > 0 {
+            eprintln!(
+                "hashify: WARNING: {} did NOT match",
+                plural(mismatched, "computed checksum", "computed checksums")
+            );
+        }
+        if verified == 0 && ignore_missing {
+            eprintln!("hashify: {}: no file was verified", name);
+        }
+    }
+    let failed = mismatched > 0
+        || unreadable > 0
+        || (matches.is_present("strict") && improper > 0)
+        || (verified == 0 && ignore_missing);
+    if failed {
+        std::process::exit(1);
+    }
 }
 
-fn name_color(s: &str) -> ColoredString {
-    match s {
-        MD5 => s.red(),
-        SHA1 | RIPEMD320 | RIPEMD160 | RIPEMD128 => s.yellow(),
-        _ => s.normal(),
+fn name_color(alg: HashAlgorithm) -> ColoredString {
+    match alg {
+        HashAlgorithm::Md5 => alg.name().red(),
+        a if a.is_legacy() => a.name().yellow(),
+        a => a.name().normal(),
     }
 }
 
-fn get_hashes_from_input(matches: &ArgMatches, hash_types: Vec<&str>) -> Vec<(String, Vec<u8>)> {
-    match matches.value_of("INPUT") {
+/// `hasher` with the --customization, the BLAKE2 --salt and --personal and
+/// the key from --hmac-key, --key or --derive-key
+fn input_hasher(matches: &ArgMatches, mut hasher: MultiHasher) -> Result<MultiHasher, String> {
+    let algorithms = hasher.algorithms();
+    if let Some(customization) = matches.value_of("customization") {
+        if !algorithms.iter().any(|a| a.is_customizable()) {
+            return Err("--customization only works with cshake128, cshake256 and k12".to_string());
+        }
+        hasher = hasher.with_customization(customization.as_bytes());
+    }
+    let blake2 = ["salt", "personal"]
+        .iter()
+        .any(|arg| matches.is_present(arg));
+    if blake2 && !algorithms.iter().all(|a| a.is_blake2()) {
+        return Err(
+            "--salt and --personal only work with blake2b and blake2s algorithms".to_string(),
+        );
+    }
+    if let Some(salt) = matches.value_of("salt") {
+        hasher = hasher.with_blake2_salt(salt.as_bytes())?;
+    }
+    if let Some(personal) = matches.value_of("personal") {
+        hasher = hasher.with_blake2_personal(personal.as_bytes())?;
+    }
+    if matches.is_present("key") && !algorithms.iter().all(|a| a.is_blake2() || a.is_blake3()) {
+        return Err("--key only works with blake2b, blake2s and blake3 algorithms".to_string());
+    }
+    if matches.is_present("derive-key") && !algorithms.iter().all(|a| a.is_blake3()) {
+        return Err("--derive-key only works with blake3 algorithms".to_string());
+    }
+    let encoding = matches
+        .value_of("key-encoding")
+        .map(|e| Code::parse(e).unwrap());
+    let stdin_input = match matches.values_of("INPUT") {
+        Some(mut inputs) => inputs.any(|i| i == "-"),
+        None => true,
+    };
+    if let Some(key) = matches.value_of("hmac-key") {
+        let key = match encoding.unwrap_or(Code::Blob) {
+            Code::Blob => read_key(key, stdin_input)?,
+            e => decode_key(&read_key(key, stdin_input)?, e)?,
+        };
+        return hasher.with_hmac_key(&key);
+    }
+    if let Some(key) = matches.value_of("key") {
+        let key = match encoding.unwrap_or(Code::LowHex) {
+            Code::Blob => read_key(key, stdin_input)?,
+            e => decode_key(&read_key(key, stdin_input)?, e)?,
+        };
+        if algorithms.iter().any(|a| a.is_blake3()) {
+            let blake3 = <[u8; 32]>::try_from(key.as_slice())
+                .map_err(|_| format!("BLAKE3 keys are 32 bytes not {}", key.len()))?;
+            hasher = hasher.with_blake3_key(&blake3);
+        }
+        if algorithms.iter().any(|a| a.is_blake2()) {
+            hasher = hasher.with_blake2_key(&key)?;
+        }
+        return Ok(hasher);
+    }
+    match matches.value_of("derive-key") {
+        Some(context) => Ok(hasher.with_blake3_context(context)),
+        None => Ok(hasher),
+    }
+}
+
+/// A key from a file, the text itself, or when `-` typed at a prompt or read
+/// from STDIN unless the input is read from there too
+fn read_key(key: &str, stdin_input: bool) -> Result<Vec<u8>, String> {
+    match key {
+        "-" => {
+            if io::stdin().is_terminal() {
+                rpassword::prompt_password("Enter Key: ")
+                    .map(|k| k.into_bytes())
+                    .map_err(|e| format!("Unable to read key - {}", e))
+            } else if stdin_input {
+                Err("The key and the input can't both be read from STDIN".to_string())
+            } else {
+                let mut bytes = Vec::new();
+                io::stdin()
+                    .read_to_end(&mut bytes)
+                    .map_err(|e| format!("Unable to read key - {}", e))?;
+                Ok(bytes)
+            }
+        }
+        _ => match get_file(key) {
+            Some(file) => {
+                std::fs::read(&file).map_err(|_| format!("Unable to read file {}", file.display()))
+            }
+            None => Ok(key.as_bytes().to_vec()),
+        },
+    }
+}
+
+fn decode_key(key: &[u8], encoding: Code) -> Result<Vec<u8>, String> {
+    Code::decode_bytes(key.trim_ascii(), encoding)
+        .map_err(|e| format!("Unable to decode key as {} - {}", encoding, e))
+}
+
+/// The pipeline from --pipeline and --text-encoding that input is transformed with
+fn input_pipeline(matches: &ArgMatches) -> Option<Pipeline> {
+    let mut spec = Vec::new();
+    if let Some(p) = matches.value_of("pipeline") {
+        spec.push(p.to_string());
+    }
+    if let Some(c) = matches.value_of("text-encoding") {
+        spec.push("from:auto".to_string());
+        match matches.is_present("bom") {
+            true => spec.push(format!("to:{}+bom", c)),
+            false => spec.push(format!("to:{}", c)),
+        }
+    }
+    match spec.is_empty() {
+        true => None,
+        false => match Pipeline::parse(spec.join(" | ")) {
+            Ok(p) => Some(p),
+            Err(e) => {
+                quit(e);
+                None
+            }
+        },
+    }
+}
+
+/// The number of bytes hashed and the digests of `input`. Unless it has to be
+/// a path, input that isn't a file is hashed as text.
+fn get_hashes_from_input(
+    matches: &ArgMatches,
+    input: Option<&str>,
+    hasher: &MultiHasher,
+    pipeline: Option<&Pipeline>,
+    path_only: bool,
+) -> Hashes {
+    match input {
+        Some(dir) if Path::new(dir).is_dir() => {
+            if pipeline.is_some() {
+                return Err("Directories can't be transformed with a pipeline".to_string());
+            }
+            hash_tree(matches, dir, hasher)?.1
+        }
         Some(text) => {
             if text == "-" {
                 let mut f = io::stdin();
-                hash_stream(&mut f, hash_types)
+                hash_input(&mut f, hasher.clone(), pipeline)
             } else {
                 match get_file(text) {
-                    Some(file) => {
-                        let mut res = Vec::new();
-                        match File::open(file.as_path()) {
-                            Ok(mut f) => res = hash_stream(&mut f, hash_types),
-                            Err(_) => {
-                                quit(format!("Unable to read file {}", file.to_str().unwrap()))
-                            }
-                        };
-                        res
+                    Some(file) if pipeline.is_none() => {
+                        let mut hasher = hasher.clone();
+                        match hasher.update_path(&file) {
+                            Ok(size) => Ok((size, hasher.finalize())),
+                            Err(e) => Err(format!(
+                                "Unable to read file {} - {}",
+                                file.to_str().unwrap(),
+                                e
+                            )),
+                        }
                     }
+                    Some(file) => match File::open(file.as_path()) {
+                        Ok(mut f) => hash_input(&mut f, hasher.clone(), pipeline),
+                        Err(e) => Err(format!(
+                            "Unable to read file {} - {}",
+                            file.to_str().unwrap(),
+                            e
+                        )),
+                    },
+                    // Pipes and devices aren't regular files but can still be read
+                    None if path_only => match File::open(text) {
+                        Ok(mut f) => hash_input(&mut f, hasher.clone(), pipeline),
+                        Err(e) => Err(format!("Unable to read file {} - {}", text, e)),
+                    },
                     None => {
                         let mut f = StringReader::new(text);
-                        hash_stream(&mut f, hash_types)
+                        hash_input(&mut f, hasher.clone(), pipeline)
                     }
                 }
             }
         }
         None => {
             let mut f = io::stdin();
-            hash_stream(&mut f, hash_types)
+            hash_input(&mut f, hasher.clone(), pipeline)
         }
     }
 }
 
-fn get_encoding_checksums(
+/// The number of bytes hashed and the digests, or why the input couldn't be read
+type Hashes = Result<(u64, Digests), String>;
+
+/// A path with its digests
+type Hashed = (String, Hashes);
+
+/// The digests of every file under `dir` with their relative paths, and the
+/// total size and tree digest of the directory which needs every file
+fn hash_tree(
     matches: &ArgMatches,
-    checksum_str: &str,
-    checksum: &[u8],
-) -> HashMap<String, Vec<u8>> {
-    let mut encoding_checksums = HashMap::new();
-    if let Some(encoding) = matches.value_of("encoding") {
-        let enc = Code::parse(encoding).unwrap();
-        match enc {
-            Code::Blob => {
-                encoding_checksums.insert(encoding.to_string(), checksum.to_vec());
+    dir: &str,
+    hasher: &MultiHasher,
+) -> Result<(Vec<Hashed>, Hashes), String> {
+    let patterns = matches
+        .values_of("exclude")
+        .map(|v| v.collect::<Vec<&str>>())
+        .unwrap_or_default();
+    let symlinks = Symlinks::parse(matches.value_of("symlinks").unwrap())?;
+    let files = Tree::new(dir)
+        .with_excludes(&patterns)?
+        .with_symlinks(symlinks)
+        .files()
+        .map_err(|e| format!("Unable to read directory {} - {}", dir, e))?;
+    let hashed = files
+        .par_iter()
+        .map(|file| {
+            let res = file
+                .hash(hasher.clone())
+                .map_err(|e| format!("Unable to read file {} - {}", file.full_path.display(), e));
+            (file.path.clone(), res)
+        })
+        .collect::<Vec<Hashed>>();
+
+    let mut size = 0;
+    let mut digests = Vec::new();
+    for (_, res) in &hashed {
+        match res {
+            Ok((s, d)) => {
+                size += s;
+                digests.push(d);
             }
-            e => match Code::decode(checksum_str, e) {
-                Ok(c) => {
-                    encoding_checksums.insert(encoding.to_string(), c);
-                }
-                Err(err) => {
-                    quit(err);
-                }
-            },
-        }
-    } else {
-        //Try to figure out the encoding
-        for v in Code::values() {
-            if let Ok(bytes) = Code::decode(checksum_str, v) {
-                encoding_checksums.insert(v.to_string(), bytes);
+            Err(_) => {
+                let tree = Err(format!("Unable to hash directory {}", dir));
+                return Ok((hashed, tree));
             }
         }
-        if encoding_checksums.is_empty() {
-            encoding_checksums.insert("blob".to_string(), checksum.to_vec());
-        }
     }
-    encoding_checksums
+    let tree = hasher
+        .algorithms()
+        .iter()
+        .enumerate()
+        .map(|(i, alg)| {
+            let pairs = files
+                .iter()
+                .zip(digests.iter())
+                .map(|(f, d)| (f, d[i].1.as_slice()))
+                .collect::<Vec<_>>();
+            (*alg, tree_digest(*alg, &pairs))
+        })
+        .collect();
+    Ok((hashed, Ok((size, tree))))
 }
 
-fn hash_stream<R: Read>(f: &mut R, hash_types: Vec<&str>) -> Vec<(String, Vec<u8>)> {
-    let mut out_hash = Vec::new();
-
-    let mut buffer = [0u8; 65536];
-
-    let mut halg: Vec<Box<dyn FixedDigest>> = Vec::with_capacity(hash_types.len());
-    for hash in &hash_types {
-        match *hash {
-            SHA3_224 => halg.push(Box::new(sha3::Sha3_224::new())),
-            SHA3_256 => halg.push(Box::new(sha3::Sha3_256::new())),
-            SHA3_384 => halg.push(Box::new(sha3::Sha3_384::new())),
-            SHA3_512 => halg.push(Box::new(sha3::Sha3_512::new())),
-            SHA2_224 => halg.push(Box::new(sha2::Sha224::new())),
-            SHA2_256 => halg.push(Box::new(sha2::Sha256::new())),
-            SHA2_512_T224 => halg.push(Box::new(sha2::Sha512_224::new())),
-            SHA2_512_T256 => halg.push(Box::new(sha2::Sha512_256::new())),
-            SHA2_384 => halg.push(Box::new(sha2::Sha384::new())),
-            SHA2_512 => halg.push(Box::new(sha2::Sha512::new())),
-            SHA1 => halg.push(Box::new(Sha1::new())),
-            BLAKE2_256 => halg.push(Box::new(Blake2_256::new())),
-            BLAKE2_384 => halg.push(Box::new(Blake2_384::new())),
-            BLAKE2_512 => halg.push(Box::new(Blake2_512::new())),
-            BLAKE3_256 => halg.push(Box::new(Blake3_256::new())),
-            BLAKE3_384 => halg.push(Box::new(Blake3_384::new())),
-            BLAKE3_512 => halg.push(Box::new(Blake3_512::new())),
-            WHIRLPOOL => halg.push(Box::new(whirlpool::Whirlpool::new())),
-            RIPEMD320 => halg.push(Box::new(ripemd::Ripemd320::new())),
-            RIPEMD160 => halg.push(Box::new(ripemd::Ripemd160::new())),
-            RIPEMD128 => halg.push(Box::new(ripemd::Ripemd128::new())),
-            MD5 => halg.push(Box::new(md5::Md5::new())),
-            e => quit(format!("Unrecognized checksum \"{}]\"", e)),
-        }
-    }
-
-    let mut read = f.read(&mut buffer);
-    while read.is_ok() {
-        let n = read.unwrap();
-
-        if n == 0 {
-            break;
-        }
-
-        for hash in halg.iter_mut() {
-            hash.update(&buffer[..n]);
+fn hash_input<R: Read>(f: &mut R, mut hasher: MultiHasher, pipeline: Option<&Pipeline>) -> Hashes {
+    let size = match pipeline {
+        Some(p) => {
+            let mut input = Vec::new();
+            f.read_to_end(&mut input)
+                .map_err(|e| format!("An error occurred while reading input - \"{}\"", e))?;
+            let data = p.run(input)?;
+            hasher.update(&data);
+            data.len() as u64
         }
-
-        read = f.read(&mut buffer);
-    }
-
-    for (hash, hash_type) in halg.iter_mut().zip(hash_types.iter()) {
-        let digest = hash.finalize_reset();
-        out_hash.push((hash_type.to_string(), digest))
-    }
-    out_hash
+        None => hasher
+            .update_reader(f)
+            .map_err(|e| format!("An error occurred while reading input - \"{}\"", e))?,
+    };
+    Ok((size, hasher.finalize()))
 }
 
 fn get_file(name: &str) -> Option<PathBuf> {
@@ -543,64 +1221,3 @@ fn quit(final_message: String) {
     println!("{}", final_message);
     std::process::exit(1);
 }
-
-trait FixedDigest {
-    fn update(&mut self, data: &[u8]);
-    fn finalize_reset(&mut self) -> Vec<u8>;
-}
-
-macro_rules! impl_fixed_digest {
-    ($($name:ident),+) => {
-        $(
-        impl FixedDigest for $name {
-            fn update(&mut self, data: &[u8]) {
-                <Self as digest::Update>::update(self, data);
-            }
-
-            fn finalize_reset(&mut self) -> Vec<u8> {
-                <Self as FixedOutputReset>::finalize_fixed_reset(self).to_vec()
-            }
-        }
-        )+
-    };
-    ($($name:path),+) => {
-        $(
-        impl FixedDigest for $name {
-            fn update(&mut self, data: &[u8]) {
-                <Self as digest::Update>::update(self, data);
-            }
-
-            fn finalize_reset(&mut self) -> Vec<u8> {
-                <Self as FixedOutputReset>::finalize_fixed_reset(self).to_vec()
-            }
-        }
-        )+
-    };
-}
-
-impl_fixed_digest!(
-    sha2::Sha224,
-    sha2::Sha256,
-    sha2::Sha384,
-    sha2::Sha512,
-    sha3::Sha3_224,
-    sha3::Sha3_256,
-    sha3::Sha3_384,
-    sha3::Sha3_512,
-    sha2::Sha512_224,
-    sha2::Sha512_256,
-    whirlpool::Whirlpool,
-    ripemd::Ripemd128,
-    ripemd::Ripemd160,
-    ripemd::Ripemd320,
-    md5::Md5
-);
-impl_fixed_digest!(
-    Blake2_256,
-    Blake2_384,
-    Blake2_512<U64>,
-    Blake3_256,
-    Blake3_384,
-    Blake3_512,
-    Sha1
-);
@@ -0,0 +1,373 @@
+use crate::{HashAlgorithm, HashAlgorithm::*, MultiHasher};
+use serious::Encoding;
+use std::io::{self, Read};
+use subtle::ConstantTimeEq;
+
+/// The layouts checksum manifests like `SHA256SUMS` are written in
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ManifestFormat {
+    /// `digest  path` or `digest *path` as written by `sha256sum`
+    Gnu,
+    /// `SHA256 (path) = digest` as written by `shasum --tag` and BSD `sha256`
+    Bsd,
+}
+
+/// One line of a checksum manifest
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Entry {
+    pub path: String,
+    pub digest: Vec<u8>,
+    /// The algorithm named by a BSD tag or every algorithm that can give the digest's length
+    pub algorithms: Vec<HashAlgorithm>,
+    /// GNU lines mark files read in binary mode with `*`
+    pub binary: bool,
+    pub format: ManifestFormat,
+}
+
+impl Entry {
+    pub fn new<P: Into<String>>(
+        path: P,
+        algorithm: HashAlgorithm,
+        digest: Vec<u8>,
+        format: ManifestFormat,
+    ) -> Self {
+        Entry {
+            path: path.into(),
+            digest,
+            algorithms: vec![algorithm],
+            binary: false,
+            format,
+        }
+    }
+
+    /// The manifest line without a newline, as read by `sha256sum -c` or
+    /// `shasum -c` for coreutils algorithms and by [`Entry::parse`] for all
+    pub fn line(&self) -> String {
+        let digest = Encoding::encode(&self.digest, Encoding::LowHex).into_string();
+        let escaped = self.path.contains(['\\', '\n', '\r']);
+        let path = match escaped {
+            true => self
+                .path
+                .replace('\\', "\\\\")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r"),
+            false => self.path.clone(),
+        };
+        let line = match self.format {
+            ManifestFormat::Gnu => {
+                format!("{} {}{}", digest, if self.binary { '*' } else { ' ' }, path)
+            }
+            ManifestFormat::Bsd => {
+                let name = match self.algorithms[0] {
+                    Blake2b => format!("BLAKE2b-{}", self.digest.len() * 8),
+                    a => bsd_name(a).to_string(),
+                };
+                format!("{} ({}) = {}", name, path, digest)
+            }
+        };
+        match escaped {
+            true => format!("\\{}", line),
+            false => line,
+        }
+    }
+
+    /// Parse a GNU or BSD manifest line, `None` for comments and blank lines
+    pub fn parse(line: &str) -> Option<Result<Self, String>> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.trim().is_empty() || line.starts_with('#') {
+            return None;
+        }
+        // Paths with a backslash or newline are escaped and the line starts with a backslash
+        let (escaped, line) = match line.strip_prefix('\\') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let entry = match Entry::parse_bsd(line) {
+            Some(entry) => entry,
+            None => Entry::parse_gnu(line),
+        };
+        Some(entry.and_then(|mut entry| {
+            if escaped {
+                entry.path = unescape(&entry.path)?;
+            }
+            Ok(entry)
+        }))
+    }
+
+    fn parse_gnu(line: &str) -> Result<Self, String> {
+        let (digest, rest) = line
+            .split_once(' ')
+            .ok_or_else(|| "Missing file name".to_string())?;
+        let (binary, path) = match rest.chars().next() {
+            Some('*') => (true, &rest[1..]),
+            Some(' ') => (false, &rest[1..]),
+            _ => (false, rest),
+        };
+        if path.is_empty() {
+            return Err("Missing file name".to_string());
+        }
+        let digest = Encoding::decode_bytes(digest.as_bytes(), Encoding::LowHex)?;
+        let algorithms = HashAlgorithm::for_digest_length(digest.len());
+        if algorithms.is_empty() {
+            return Err(format!("No algorithm has a {} byte digest", digest.len()));
+        }
+        Ok(Entry {
+            path: path.to_string(),
+            digest,
+            algorithms,
+            binary,
+            format: ManifestFormat::Gnu,
+        })
+    }
+
+    /// `None` when the line isn't tagged so it can be tried as GNU
+    fn parse_bsd(line: &str) -> Option<Result<Self, String>> {
+        let (tag, rest) = line.split_once(" (")?;
+        let (path, digest) = rest.rsplit_once(") = ")?;
+        if tag.is_empty() || tag.contains(char::is_whitespace) {
+            return None;
+        }
+        Some(bsd_tag(tag).and_then(|algorithm| {
+            // Newer coreutils can write tagged digests in padded standard base64
+            let digest =
+                Encoding::decode_bytes(digest.as_bytes(), Encoding::LowHex).or_else(|e| {
+                    let url = digest
+                        .trim_end_matches('=')
+                        .replace('+', "-")
+                        .replace('/', "_");
+                    Encoding::decode_bytes(url.as_bytes(), Encoding::Base64).map_err(|_| e)
+                })?;
+            if !algorithm.lengths().contains(&digest.len()) {
+                return Err(format!(
+                    "{} digests are {} bytes not {}",
+                    algorithm,
+                    algorithm.output_size(),
+                    digest.len()
+                ));
+            }
+            // b2sum's tags are also BLAKE2b at any length
+            let algorithms = match algorithm {
+                Blake2b256 | Blake2b384 | Blake2b512 => vec![algorithm, Blake2b],
+                a => vec![a],
+            };
+            Ok(Entry {
+                path: path.to_string(),
+                digest,
+                algorithms,
+                binary: false,
+                format: ManifestFormat::Bsd,
+            })
+        }))
+    }
+
+    /// Hash everything from `reader` and check it against the digest. A GNU
+    /// line passes when any algorithm with the digest's length matches.
+    pub fn verify_reader<R: Read>(&self, reader: &mut R) -> io::Result<bool> {
+        let mut hasher = MultiHasher::new(&self.algorithms).with_length(self.digest.len());
+        hasher.update_reader(reader)?;
+        Ok(hasher
+            .finalize()
+            .iter()
+            .any(|(_, d)| d.ct_eq(&self.digest).into()))
+    }
+}
+
+/// The algorithm for a BSD tag like `SHA256`, `SHA3-256` or `BLAKE2b`,
+/// or one of our own names
+fn bsd_tag(tag: &str) -> Result<HashAlgorithm, String> {
+    match tag.to_ascii_uppercase().as_str() {
+        "MD5" => Ok(Md5),
+        "SHA1" => Ok(Sha1),
+        "SHA224" => Ok(Sha2_224),
+        "SHA256" => Ok(Sha2_256),
+        "SHA384" => Ok(Sha2_384),
+        "SHA512" => Ok(Sha2_512),
+        "SHA512T224" | "SHA512/224" | "SHA512-224" => Ok(Sha2_512T224),
+        "SHA512T256" | "SHA512/256" | "SHA512-256" => Ok(Sha2_512T256),
+        "RMD128" => Ok(Ripemd128),
+        "RMD160" => Ok(Ripemd160),
+        "RMD320" => Ok(Ripemd320),
+        "BLAKE2B" | "BLAKE2B-512" => Ok(Blake2b512),
+        "BLAKE2B-256" => Ok(Blake2b256),
+        "BLAKE2B-384" => Ok(Blake2b384),
+        // b2sum -l writes the length in bits
+        t if t.starts_with("BLAKE2B-") && t[8..].parse::<usize>().is_ok() => Ok(Blake2b),
+        // b3sum writes 32 bytes by default and more with --length
+        "BLAKE3" => Ok(Blake3),
+        _ => HashAlgorithm::parse(tag.to_ascii_lowercase()),
+    }
+}
+
+/// The tag coreutils or BSD tools write for an algorithm, our own name otherwise
+fn bsd_name(algorithm: HashAlgorithm) -> &'static str {
+    match algorithm {
+        Md5 => "MD5",
+        Sha1 => "SHA1",
+        Sha2_224 => "SHA224",
+        Sha2_256 => "SHA256",
+        Sha2_384 => "SHA384",
+        Sha2_512 => "SHA512",
+        Sha2_512T224 => "SHA512t224",
+        Sha2_512T256 => "SHA512t256",
+        Ripemd128 => "RMD128",
+        Ripemd160 => "RMD160",
+        Ripemd320 => "RMD320",
+        Blake2b512 => "BLAKE2b",
+        Blake2b256 => "BLAKE2b-256",
+        Blake2b384 => "BLAKE2b-384",
+        Blake3 => "BLAKE3",
+        a => a.name(),
+    }
+}
+
+fn unescape(path: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\\') => out.push('\\'),
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                _ => return Err(format!("Invalid escape in file name \"{}\"", path)),
+            },
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The SHA-256 of `abc` in hex
+    fn hex() -> String {
+        Encoding::encode(Sha2_256.digest(b"abc"), Encoding::LowHex).into_string()
+    }
+
+    #[test]
+    fn gnu() {
+        let hex = hex();
+        let entry = Entry::parse(&format!("{}  release.tar.gz\n", hex))
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.path, "release.tar.gz");
+        assert_eq!(entry.format, ManifestFormat::Gnu);
+        assert!(!entry.binary);
+        assert!(entry.algorithms.contains(&Sha2_256));
+        assert!(entry.algorithms.contains(&Sha3_256));
+        assert!(entry.verify_reader(&mut &b"abc"[..]).unwrap());
+        assert!(!entry.verify_reader(&mut &b"abd"[..]).unwrap());
+
+        let entry = Entry::parse(&format!("{} *dir/a b.iso", hex.to_uppercase()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.path, "dir/a b.iso");
+        assert!(entry.binary);
+
+        let entry = Entry::parse(&format!("\\{}  a\\\\b\\nc", hex))
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.path, "a\\b\nc");
+    }
+
+    #[test]
+    fn bsd() {
+        let hex = hex();
+        let entry = Entry::parse(&format!("SHA256 (x (1).bin) = {}", hex))
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.path, "x (1).bin");
+        assert_eq!(entry.format, ManifestFormat::Bsd);
+        assert_eq!(entry.algorithms, vec![Sha2_256]);
+        assert!(entry.verify_reader(&mut &b"abc"[..]).unwrap());
+
+        let base64 = Encoding::encode(Md5.digest(b"abc"), Encoding::Base64).into_string();
+        let entry = Entry::parse(&format!("MD5 (a) = {}", base64))
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.algorithms, vec![Md5]);
+        let entry = Entry::parse("MD5 (a) = kAFQmDzST7DWlj99KOF/cg==")
+            .unwrap()
+            .unwrap();
+        assert!(entry.verify_reader(&mut &b"abc"[..]).unwrap());
+        let entry = Entry::parse(&format!("sha3-256 (a) = {}", hex))
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.algorithms, vec![Sha3_256]);
+    }
+
+    #[test]
+    fn lines() {
+        for a in HashAlgorithm::values() {
+            for format in [ManifestFormat::Gnu, ManifestFormat::Bsd] {
+                for path in ["a b.iso", "x (1)", "a\\b\nc"] {
+                    let entry = Entry::new(path, a, a.digest(path), format);
+                    let parsed = Entry::parse(&entry.line()).unwrap().unwrap();
+                    assert_eq!(parsed.path, path, "{}", entry.line());
+                    assert_eq!(parsed.digest, entry.digest);
+                    assert!(parsed.algorithms.contains(&a));
+                }
+            }
+        }
+        let hex = hex();
+        let entry = Entry::new("a", Sha2_256, Sha2_256.digest(b"abc"), ManifestFormat::Gnu);
+        assert_eq!(entry.line(), format!("{}  a", hex));
+        let entry = Entry::new("a", Sha2_256, Sha2_256.digest(b"abc"), ManifestFormat::Bsd);
+        assert_eq!(entry.line(), format!("SHA256 (a) = {}", hex));
+    }
+
+    #[test]
+    fn xof_lengths() {
+        let entry = Entry::new(
+            "a",
+            Shake256,
+            Shake256.xof(100, b"").unwrap().finalize().into_vec(),
+            ManifestFormat::Gnu,
+        );
+        let parsed = Entry::parse(&entry.line()).unwrap().unwrap();
+        assert!(parsed.algorithms.contains(&Shake256));
+        assert!(!parsed.verify_reader(&mut &b"abc"[..]).unwrap());
+        assert!(parsed.verify_reader(&mut &b""[..]).unwrap());
+        let entry = Entry::new("a", Blake3, Blake3.digest(b"abc"), ManifestFormat::Bsd);
+        let parsed = Entry::parse(&entry.line()).unwrap().unwrap();
+        assert!(entry.line().starts_with("BLAKE3 (a) = "));
+        assert_eq!(parsed.algorithms, vec![Blake3]);
+    }
+
+    #[test]
+    fn blake2_lengths() {
+        let digest = Blake2b
+            .blake2(20, b"", b"", b"")
+            .unwrap()
+            .finalize()
+            .into_vec();
+        let entry = Entry::new("a", Blake2b, digest, ManifestFormat::Bsd);
+        assert!(entry.line().starts_with("BLAKE2b-160 (a) = "));
+        let parsed = Entry::parse(&entry.line()).unwrap().unwrap();
+        assert_eq!(parsed.algorithms, vec![Blake2b]);
+        assert!(parsed.verify_reader(&mut &b""[..]).unwrap());
+        let b2sum =
+            "BLAKE2b (a) = 786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419\
+                     d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce";
+        let entry = Entry::parse(b2sum).unwrap().unwrap();
+        assert_eq!(entry.algorithms, vec![Blake2b512, Blake2b]);
+        assert!(entry.verify_reader(&mut &b""[..]).unwrap());
+    }
+
+    #[test]
+    fn invalid() {
+        let hex = hex();
+        assert!(Entry::parse("# comment").is_none());
+        assert!(Entry::parse("").is_none());
+        assert!(Entry::parse(&hex).unwrap().is_err());
+        assert!(Entry::parse("abcd  short").unwrap().is_err());
+        assert!(Entry::parse(&format!("MD5 (a) = {}", hex))
+            .unwrap()
+            .is_err());
+        assert!(Entry::parse(&format!("SHA4 (a) = {}", hex))
+            .unwrap()
+            .is_err());
+    }
+}
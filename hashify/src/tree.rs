@@ -0,0 +1,295 @@
+use crate::{Digests, HashAlgorithm, MultiHasher};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::fmt::Display;
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// What to do with symbolic links found in a directory
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Symlinks {
+    /// Hash the file or directory the link points to
+    Follow,
+    /// Leave links out
+    Skip,
+    /// Hash the link's target path instead of what it points to
+    Link,
+}
+
+impl Symlinks {
+    pub fn parse<I: AsRef<str>>(s: I) -> Result<Self, String> {
+        match s.as_ref() {
+            "follow" => Ok(Symlinks::Follow),
+            "skip" => Ok(Symlinks::Skip),
+            "link" => Ok(Symlinks::Link),
+            s => Err(format!("Unrecognized symlink policy - \"{}\"", s)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symlinks::Follow => "follow",
+            Symlinks::Skip => "skip",
+            Symlinks::Link => "link",
+        }
+    }
+}
+
+impl Display for Symlinks {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A file found under a directory
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TreeFile {
+    /// Relative to the directory with `/` separators
+    pub path: String,
+    /// Where to read the file, the directory joined with `path`
+    pub full_path: PathBuf,
+    /// Whether anyone may execute the file, always false for links and where
+    /// there are no permission bits so the digest is the same on every platform
+    pub executable: bool,
+    /// The target of a link kept with [`Symlinks::Link`]
+    pub link: Option<PathBuf>,
+}
+
+impl TreeFile {
+    /// The number of bytes hashed and the digests of the file, or of the
+    /// target path for a link, from a new `hasher`
+    pub fn hash(&self, mut hasher: MultiHasher) -> io::Result<(u64, Digests)> {
+        let size = match &self.link {
+            Some(target) => {
+                let target = target.to_string_lossy();
+                hasher.update(target.as_bytes());
+                target.len() as u64
+            }
+            None => hasher.update_path(&self.full_path)?,
+        };
+        Ok((size, hasher.finalize()))
+    }
+}
+
+/// Lists the files under a directory in a deterministic order
+#[derive(Debug, Clone)]
+pub struct Tree {
+    root: PathBuf,
+    excludes: Gitignore,
+    symlinks: Symlinks,
+}
+
+impl Tree {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Tree {
+            root: root.as_ref().to_path_buf(),
+            excludes: Gitignore::empty(),
+            symlinks: Symlinks::Follow,
+        }
+    }
+
+    /// Leave out paths matching these gitignore style patterns like
+    /// `target/`, `*.log` or `/build`
+    pub fn with_excludes<S: AsRef<str>>(mut self, patterns: &[S]) -> Result<Self, String> {
+        let mut builder = GitignoreBuilder::new(&self.root);
+        for pattern in patterns {
+            builder
+                .add_line(None, pattern.as_ref())
+                .map_err(|e| format!("Invalid exclude pattern - {}", e))?;
+        }
+        self.excludes = builder
+            .build()
+            .map_err(|e| format!("Invalid exclude pattern - {}", e))?;
+        Ok(self)
+    }
+
+    pub fn with_symlinks(mut self, symlinks: Symlinks) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+
+    /// Every file under the directory sorted by relative path. Directories
+    /// only count through the files in them so empty ones are left out.
+    /// A followed link back to one of its own parent directories is skipped.
+    pub fn files(&self) -> io::Result<Vec<TreeFile>> {
+        let walker = WalkDir::new(&self.root)
+            .follow_links(self.symlinks == Symlinks::Follow)
+            .into_iter()
+            .filter_entry(|e| {
+                e.depth() == 0
+                    || !self
+                        .excludes
+                        .matched(relative(&self.root, e.path()), e.file_type().is_dir())
+                        .is_ignore()
+            });
+        let mut files = Vec::new();
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) if e.loop_ancestor().is_some() => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let file_type = entry.file_type();
+            let link = match (file_type.is_symlink(), self.symlinks) {
+                (true, Symlinks::Skip) => continue,
+                (true, _) => Some(fs::read_link(entry.path())?),
+                (false, _) if file_type.is_file() => None,
+                _ => continue,
+            };
+            let path = relative(&self.root, entry.path())
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push(TreeFile {
+                path,
+                full_path: entry.path().to_path_buf(),
+                executable: link.is_none() && executable(&entry.metadata()?),
+                link,
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+}
+
+fn relative<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+#[cfg(unix)]
+fn executable(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn executable(_: &Metadata) -> bool {
+    false
+}
+
+/// One digest for a whole directory. Each file adds a kind byte, `l` for
+/// links and `f` otherwise, `x` if it's executable or `-`, the length of its
+/// path as 8 big-endian bytes, the path and the digest of its contents, in
+/// the order of `files`. Algorithms of any length give as many bytes as the
+/// file digests.
+pub fn tree_digest(algorithm: HashAlgorithm, files: &[(&TreeFile, &[u8])]) -> Vec<u8> {
+    let len = files
+        .first()
+        .map_or(algorithm.output_size(), |(_, d)| d.len());
+    let mut hasher = algorithm
+        .xof(len, b"")
+        .or_else(|| algorithm.blake2(len, b"", b"", b"").ok())
+        .unwrap_or_else(|| algorithm.hasher());
+    for (file, digest) in files {
+        hasher.update(if file.link.is_some() { b"l" } else { b"f" });
+        hasher.update(if file.executable { b"x" } else { b"-" });
+        hasher.update(&(file.path.len() as u64).to_be_bytes());
+        hasher.update(file.path.as_bytes());
+        hasher.update(digest);
+    }
+    hasher.finalize().into_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashAlgorithm::*;
+
+    fn digest(tree: &Tree) -> Vec<u8> {
+        let files = tree.files().unwrap();
+        let digests = files
+            .iter()
+            .map(|f| f.hash(MultiHasher::new(&[Sha2_256])).unwrap().1.remove(0).1)
+            .collect::<Vec<_>>();
+        let pairs = files
+            .iter()
+            .zip(digests.iter())
+            .map(|(f, d)| (f, d.as_slice()))
+            .collect::<Vec<_>>();
+        tree_digest(Sha2_256, &pairs)
+    }
+
+    #[test]
+    fn tree() {
+        let root = std::env::temp_dir().join(format!("hashify-tree-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("a-b"), b"1").unwrap();
+        fs::write(root.join("src/main.rs"), b"2").unwrap();
+        fs::write(root.join("src/nested/x.log"), b"3").unwrap();
+        fs::write(root.join("target/out"), b"4").unwrap();
+
+        let tree = Tree::new(&root);
+        let files = tree.files().unwrap();
+        let paths = files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            ["a-b", "src/main.rs", "src/nested/x.log", "target/out"]
+        );
+        assert_eq!(
+            files[0].hash(MultiHasher::new(&[Md5])).unwrap().1[0].1,
+            Md5.digest(b"1")
+        );
+        let before = digest(&tree);
+        assert_eq!(before, digest(&Tree::new(&root)));
+
+        let excluded = Tree::new(&root)
+            .with_excludes(&["target/", "*.log"])
+            .unwrap();
+        let paths = excluded
+            .files()
+            .unwrap()
+            .into_iter()
+            .map(|f| f.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["a-b", "src/main.rs"]);
+        assert_ne!(digest(&excluded), before);
+
+        fs::write(root.join("src/main.rs"), b"5").unwrap();
+        assert_ne!(digest(&tree), before);
+        fs::write(root.join("src/main.rs"), b"2").unwrap();
+        fs::rename(root.join("a-b"), root.join("a-c")).unwrap();
+        assert_ne!(digest(&tree), before);
+        fs::rename(root.join("a-c"), root.join("a-b")).unwrap();
+        assert_eq!(digest(&tree), before);
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("src", root.join("link")).unwrap();
+            let count = |s| Tree::new(&root).with_symlinks(s).files().unwrap().len();
+            assert_eq!(count(Symlinks::Skip), 4);
+            assert_eq!(count(Symlinks::Follow), 6);
+            let files = Tree::new(&root)
+                .with_symlinks(Symlinks::Link)
+                .files()
+                .unwrap();
+            let link = files.iter().find(|f| f.path == "link").unwrap();
+            assert_eq!(link.link, Some(PathBuf::from("src")));
+            assert_eq!(
+                link.hash(MultiHasher::new(&[Md5])).unwrap().1[0].1,
+                Md5.digest(b"src")
+            );
+            fs::remove_file(root.join("link")).unwrap();
+
+            use std::os::unix::fs::PermissionsExt;
+            let chmod = |mode| {
+                fs::set_permissions(root.join("a-b"), fs::Permissions::from_mode(mode)).unwrap()
+            };
+            chmod(0o600);
+            assert_eq!(digest(&tree), before);
+            chmod(0o755);
+            assert!(tree.files().unwrap()[0].executable);
+            assert_ne!(digest(&tree), before);
+            chmod(0o644);
+
+            std::os::unix::fs::symlink("..", root.join("src/up")).unwrap();
+            assert_eq!(count(Symlinks::Follow), 4);
+            assert_eq!(count(Symlinks::Link), 5);
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{ByteOrder, HashAlgorithm, MultiHasher};
+use serious::Encoding;
+use std::io::{self, Read};
+use subtle::ConstantTimeEq;
+
+/// One comparison of a digest with the checksum
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Trial {
+    pub algorithm: HashAlgorithm,
+    pub byte_order: ByteOrder,
+    /// The encoding the checksum was decoded with
+    pub encoding: Encoding,
+    pub passed: bool,
+}
+
+/// Checks inputs against a checksum whose encoding, algorithm and byte order
+/// may be unknown. Every combination that could have produced it is tried.
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    checksums: Vec<(Encoding, Vec<u8>)>,
+    algorithms: Vec<HashAlgorithm>,
+    byte_orders: Vec<ByteOrder>,
+}
+
+impl Verifier {
+    /// Decode the checksum with `encoding`, or with every encoding that accepts it
+    /// when `None`. Without a decodable encoding the checksum is used as raw bytes.
+    pub fn new(checksum: &[u8], encoding: Option<Encoding>) -> Result<Self, String> {
+        // Checksum files usually end with a newline
+        let text = checksum.trim_ascii();
+        let checksums = match encoding {
+            Some(Encoding::Blob) => vec![(Encoding::Blob, checksum.to_vec())],
+            Some(e) => vec![(e, Encoding::decode_bytes(text, e)?)],
+            None => {
+                // Skip encodings like json that decode plain text to itself, that's blob
+                let mut checksums = Encoding::values()
+                    .into_iter()
+                    .filter_map(|e| match Encoding::decode_bytes(text, e) {
+                        Ok(bytes) if bytes != checksum && bytes != text => Some((e, bytes)),
+                        _ => None,
+                    })
+                    .collect::<Vec<(Encoding, Vec<u8>)>>();
+                if checksums.is_empty() {
+                    checksums.push((Encoding::Blob, checksum.to_vec()));
+                }
+                checksums
+            }
+        };
+        let mut algorithms = Vec::new();
+        for (_, c) in &checksums {
+            for a in HashAlgorithm::for_digest_length(c.len()) {
+                if !algorithms.contains(&a) {
+                    algorithms.push(a);
+                }
+            }
+        }
+        // Fixed length algorithms are named first when both pass
+        algorithms.sort_by_key(|a| a.lengths().start() < a.lengths().end());
+        Ok(Verifier {
+            checksums,
+            algorithms,
+            byte_orders: vec![ByteOrder::Big, ByteOrder::Little],
+        })
+    }
+
+    /// Only try these algorithms instead of those matching the checksum length
+    pub fn with_algorithms(mut self, algorithms: &[HashAlgorithm]) -> Self {
+        self.algorithms = algorithms.to_vec();
+        self
+    }
+
+    /// Only try this byte order instead of both
+    pub fn with_byte_order(mut self, byte_order: ByteOrder) -> Self {
+        self.byte_orders = vec![byte_order];
+        self
+    }
+
+    /// The algorithms that will be tried, empty when no algorithm has the checksum's length
+    pub fn algorithms(&self) -> &[HashAlgorithm] {
+        &self.algorithms
+    }
+
+    /// The candidate checksums with the encoding each was decoded from
+    pub fn checksums(&self) -> &[(Encoding, Vec<u8>)] {
+        &self.checksums
+    }
+
+    /// The longest candidate checksum in bytes, how much to read from
+    /// extendable algorithms
+    pub fn length(&self) -> usize {
+        self.checksums
+            .iter()
+            .map(|(_, c)| c.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// A hasher for every algorithm that will be tried. Extendable algorithms
+    /// give the longest candidate's length and BLAKE2b and BLAKE2s at any
+    /// length give one digest for each candidate length they can.
+    pub fn hasher(&self) -> MultiHasher {
+        let mut sizes = Vec::new();
+        for a in &self.algorithms {
+            let lengths = a.lengths();
+            if a.is_extendable() {
+                sizes.push((*a, self.length().max(1)));
+            } else if lengths.start() < lengths.end() {
+                let mut fits = self
+                    .checksums
+                    .iter()
+                    .map(|(_, c)| c.len())
+                    .filter(|len| lengths.contains(len))
+                    .collect::<Vec<_>>();
+                fits.sort_unstable();
+                fits.dedup();
+                if fits.is_empty() {
+                    fits.push(a.output_size());
+                }
+                sizes.extend(fits.into_iter().map(|len| (*a, len)));
+            } else {
+                sizes.push((*a, a.output_size()));
+            }
+        }
+        MultiHasher::sized(&sizes).expect("lengths the algorithms can give")
+    }
+
+    /// Compare digests with the checksum, passing trials first. Digests
+    /// from extendable algorithms are cut to the checksum's length since
+    /// their shorter outputs are the start of longer ones. An algorithm with
+    /// a digest of a checksum's length isn't compared with its other digests.
+    pub fn verify(&self, digests: &[(HashAlgorithm, Vec<u8>)]) -> Vec<Trial> {
+        let mut trials = Vec::new();
+        for (algorithm, digest) in digests {
+            for (encoding, checksum) in &self.checksums {
+                let digest = match algorithm.is_extendable() && digest.len() > checksum.len() {
+                    true => &digest[..checksum.len()],
+                    false => &digest[..],
+                };
+                // Skip the lengths meant for other candidates
+                let other = digests
+                    .iter()
+                    .any(|(a, d)| a == algorithm && d.len() == checksum.len());
+                if digest.len() != checksum.len() && other {
+                    continue;
+                }
+                for byte_order in &self.byte_orders {
+                    trials.push(Trial {
+                        algorithm: *algorithm,
+                        byte_order: *byte_order,
+                        encoding: *encoding,
+                        // Constant time so a MAC can't be guessed byte by byte
+                        passed: byte_order.apply(digest).ct_eq(checksum).into(),
+                    });
+                }
+            }
+        }
+        trials.sort_by_key(|t| !t.passed);
+        trials
+    }
+
+    /// Hash everything from `reader` and compare it with the checksum
+    pub fn verify_reader<R: Read>(&self, reader: &mut R) -> io::Result<Vec<Trial>> {
+        let mut hasher = self.hasher();
+        hasher.update_reader(reader)?;
+        Ok(self.verify(&hasher.finalize()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashAlgorithm::*;
+
+    #[test]
+    fn verifier() {
+        let digest = Sha2_256.digest(b"abc");
+        let hex = Encoding::encode(&digest, Encoding::LowHex).into_string();
+
+        let verifier = Verifier::new(format!("{}\n", hex).as_bytes(), None).unwrap();
+        assert!(verifier.algorithms().contains(&Sha2_256));
+        assert!(verifier.algorithms().contains(&Blake3_256));
+        assert!(!verifier.algorithms().contains(&Sha1));
+        let trials = verifier.verify_reader(&mut &b"abc"[..]).unwrap();
+        // Other hex variants accept the checksum too
+        let passed = trials.iter().filter(|t| t.passed).collect::<Vec<_>>();
+        assert!(passed
+            .iter()
+            .all(|t| t.algorithm == Sha2_256 && t.byte_order == ByteOrder::Big));
+        assert!(passed.iter().any(|t| t.encoding == Encoding::LowHex));
+        assert!(!trials.last().unwrap().passed);
+
+        let mut reversed = digest.clone();
+        reversed.reverse();
+        let base64 = Encoding::encode(&reversed, Encoding::Base64).into_string();
+        let verifier = Verifier::new(base64.as_bytes(), Some(Encoding::Base64))
+            .unwrap()
+            .with_algorithms(&[Sha3_256, Sha2_256]);
+        let trials = verifier.verify(&verifier.hasher().finalize());
+        assert!(trials.iter().all(|t| !t.passed));
+        let mut hasher = verifier.hasher();
+        hasher.update(b"abc");
+        let trials = verifier.verify(&hasher.finalize());
+        assert_eq!(trials.iter().filter(|t| t.passed).count(), 1);
+        assert_eq!(trials[0].byte_order, ByteOrder::Little);
+        assert!(verifier
+            .clone()
+            .with_byte_order(ByteOrder::Big)
+            .verify_reader(&mut &b"abc"[..])
+            .unwrap()
+            .iter()
+            .all(|t| !t.passed));
+
+        let mut shake = Shake256.xof(100, b"").unwrap();
+        shake.update(b"abc");
+        let base64 = Encoding::encode(shake.finalize(), Encoding::Base64).into_string();
+        let verifier = Verifier::new(base64.as_bytes(), None).unwrap();
+        assert!(verifier.algorithms().contains(&Shake256));
+        assert!(verifier.length() >= 100);
+        let trials = verifier.verify_reader(&mut &b"abc"[..]).unwrap();
+        assert!(trials[0].passed);
+        assert!(trials
+            .iter()
+            .filter(|t| t.passed)
+            // cSHAKE without a customization string is SHAKE
+            .all(
+                |t| [Shake256, CShake256].contains(&t.algorithm) && t.byte_order == ByteOrder::Big
+            ));
+
+        assert!(Verifier::new(b"zz", Some(Encoding::LowHex)).is_err());
+        assert!(Verifier::new(b"abc", None).unwrap().algorithms().is_empty());
+    }
+}
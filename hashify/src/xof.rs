@@ -0,0 +1,146 @@
+use crate::HashAlgorithm::{self, *};
+use digest::{core_api::CoreWrapper, DynDigest, ExtendableOutput, InvalidBufferSize, Update};
+use sha3::{CShake128, CShake128Core, CShake256, CShake256Core, Shake128, Shake256};
+use tiny_keccak::{Hasher, KangarooTwelve};
+
+#[derive(Clone)]
+enum Sponge {
+    Shake128(Shake128),
+    Shake256(Shake256),
+    CShake128(CShake128),
+    CShake256(CShake256),
+    K12(Box<KangarooTwelve<Vec<u8>>>),
+    Blake3(Box<blake3::Hasher>),
+}
+
+/// An extendable output function read to a length chosen at runtime
+#[derive(Clone)]
+pub(crate) struct Xof {
+    initial: Sponge,
+    state: Sponge,
+    len: usize,
+}
+
+impl Xof {
+    /// `None` for algorithms with a fixed length. The customization string
+    /// only changes cSHAKE and KangarooTwelve.
+    pub(crate) fn new(algorithm: HashAlgorithm, len: usize, customization: &[u8]) -> Option<Self> {
+        let sponge = match algorithm {
+            Shake128 => Sponge::Shake128(Shake128::default()),
+            Shake256 => Sponge::Shake256(Shake256::default()),
+            CShake128 => {
+                Sponge::CShake128(CoreWrapper::from_core(CShake128Core::new(customization)))
+            }
+            CShake256 => {
+                Sponge::CShake256(CoreWrapper::from_core(CShake256Core::new(customization)))
+            }
+            K12 => Sponge::K12(Box::new(KangarooTwelve::new(customization.to_vec()))),
+            Blake3 => Sponge::Blake3(Box::default()),
+            _ => return None,
+        };
+        Some(Xof {
+            initial: sponge.clone(),
+            state: sponge,
+            len,
+        })
+    }
+
+    fn finish(state: Sponge, out: &mut [u8]) {
+        match state {
+            Sponge::Shake128(s) => s.finalize_xof_into(out),
+            Sponge::Shake256(s) => s.finalize_xof_into(out),
+            Sponge::CShake128(s) => s.finalize_xof_into(out),
+            Sponge::CShake256(s) => s.finalize_xof_into(out),
+            Sponge::K12(s) => s.finalize(out),
+            Sponge::Blake3(s) => s.finalize_xof().fill(out),
+        }
+    }
+}
+
+impl DynDigest for Xof {
+    fn update(&mut self, data: &[u8]) {
+        match &mut self.state {
+            Sponge::Shake128(s) => s.update(data),
+            Sponge::Shake256(s) => s.update(data),
+            Sponge::CShake128(s) => s.update(data),
+            Sponge::CShake256(s) => s.update(data),
+            Sponge::K12(s) => s.update(data),
+            Sponge::Blake3(s) => {
+                s.update(data);
+            }
+        }
+    }
+
+    fn finalize_into(self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        if buf.len() != self.len {
+            return Err(InvalidBufferSize);
+        }
+        Xof::finish(self.state, buf);
+        Ok(())
+    }
+
+    fn finalize_into_reset(&mut self, out: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        if out.len() != self.len {
+            return Err(InvalidBufferSize);
+        }
+        let state = std::mem::replace(&mut self.state, self.initial.clone());
+        Xof::finish(state, out);
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.state = self.initial.clone();
+    }
+
+    fn output_size(&self) -> usize {
+        self.len
+    }
+
+    fn box_clone(&self) -> Box<dyn DynDigest> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serious::Encoding;
+
+    fn hex(algorithm: HashAlgorithm, len: usize, customization: &[u8], data: &[u8]) -> String {
+        let mut xof = Xof::new(algorithm, len, customization).unwrap();
+        DynDigest::update(&mut xof, data);
+        Encoding::encode(Box::new(xof).finalize(), Encoding::LowHex).into_string()
+    }
+
+    #[test]
+    fn xof() {
+        assert_eq!(
+            hex(Shake128, 32, b"", b""),
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26"
+        );
+        assert_eq!(
+            hex(Shake256, 16, b"", b""),
+            "46b9dd2b0ba88d13233b3feb743eeb24"
+        );
+        // NIST SP 800-185 cSHAKE128 sample 1
+        assert_eq!(
+            hex(CShake128, 32, b"Email Signature", &[0, 1, 2, 3]),
+            "c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5"
+        );
+        // RFC 9861 KangarooTwelve
+        assert_eq!(
+            hex(K12, 32, b"", b""),
+            "1ac2d450fc3b4205d19da7bfca1b37513c0803577ac7167f06fe2ce1f0ef39e5"
+        );
+        assert_eq!(
+            hex(Blake3, 32, b"", b"abc"),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        // Longer outputs start with the shorter ones
+        let long = hex(Shake256, 300, b"", b"abc");
+        assert_eq!(long.len(), 600);
+        assert!(long.starts_with(&hex(Shake256, 64, b"", b"abc")));
+        assert_ne!(hex(K12, 32, b"a", b"abc"), hex(K12, 32, b"", b"abc"));
+        assert!(Xof::new(Sha2_256, 32, b"").is_none());
+    }
+}
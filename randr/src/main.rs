@@ -57,6 +57,13 @@ fn main() {
 
     match encoding {
         Code::Blob => io::stdout().write_all(data.as_slice()).unwrap(),
-        e => println!("{}", Code::encode(data, e).into_string()),
+        e => match Code::encode(data, e).try_into_string() {
+            Ok(s) => println!("{}", s),
+            Err(e) => {
+                io::stdout().write_all(e.as_bytes()).unwrap();
+                io::stdout().flush().unwrap();
+                std::process::exit(1);
+            }
+        },
     };
 }
@@ -1,11 +1,11 @@
-use clap::{App, Arg};
+use clap::{App, Arg, ArgMatches};
 use sha2::digest::Digest;
 use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{self, Read};
 use std::path::PathBuf;
 
-use serious::Encoding as Code;
+use serious::{Alphabet, Encoding as Code};
 
 pub enum CommandLineArgs {
     Bytes(usize),
@@ -14,21 +14,15 @@ pub enum CommandLineArgs {
 }
 
 pub fn parse_cmd_line() -> Result<BTreeMap<&'static str, CommandLineArgs>, String> {
-    let encodings = vec![
-        Code::Blob.to_string(),
-        Code::Binary.to_string(),
-        Code::Base10.to_string(),
-        Code::Base58.to_string(),
-        Code::Base62.to_string(),
-        Code::Base64.to_string(),
-        Code::Base64Url.to_string(),
-        Code::BitCoin.to_string(),
-        Code::Flickr.to_string(),
-        Code::LowHex.to_string(),
-        Code::Monero.to_string(),
-        Code::Ripple.to_string(),
-    ];
-    let enc_ref = encodings.iter().map(|e| e.as_str()).collect::<Vec<&str>>();
+    let encodings = Code::values()
+        .iter()
+        .map(|e| e.name())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    let encoding_help = format!(
+        "The output encoding to use. [possible values: {}]",
+        encodings
+    );
     let matches = App::new("randr")
         .version("0.1")
         .author("Michael Lodder")
@@ -38,20 +32,40 @@ pub fn parse_cmd_line() -> Result<BTreeMap<&'static str, CommandLineArgs>, Strin
                 .help("The number of bytes to randomly generate")
                 .short("b")
                 .long("bytes")
-                .required(true)
+                .required_unless("mnemonic")
                 .takes_value(true),
         )
         .arg(
             Arg::with_name("encoding")
-                .help("The output encoding to use.")
+                .help(&encoding_help)
                 .short("e")
                 .long("encoding")
                 .required(false)
                 .takes_value(true)
-                .possible_values(enc_ref.as_slice())
+                .validator(|e| Code::parse(e).map(|_| ()))
                 .value_delimiter(",")
                 .default_value("hex"),
         )
+        .arg(
+            Arg::with_name("mnemonic")
+                .help("Generate a BIP39 mnemonic with this many words")
+                .short("m")
+                .long("mnemonic")
+                .required(false)
+                .takes_value(true)
+                .possible_values(&["12", "15", "18", "21", "24"])
+                .conflicts_with_all(&["bytes", "encoding"]),
+        )
+        .arg(
+            Arg::with_name("alphabet")
+                .help("Encode with these symbols instead, like 0123456789abcdefghijklmnopqrstuvwxyz for base36")
+                .short("a")
+                .long("alphabet")
+                .required(false)
+                .takes_value(true)
+                .validator(|a| Alphabet::new(a).map(|_| ()))
+                .conflicts_with_all(&["encoding", "mnemonic"]),
+        )
         .arg(
             Arg::with_name("seed")
                 .help("A starting value to generate random data")
@@ -64,6 +78,14 @@ pub fn parse_cmd_line() -> Result<BTreeMap<&'static str, CommandLineArgs>, Strin
 
     let mut args = BTreeMap::new();
 
+    if let Some(words) = matches.value_of("mnemonic") {
+        // Each word holds 11 bits, 32 of every 33 bits are entropy
+        let words = words.parse::<usize>().unwrap();
+        args.insert("bytes", CommandLineArgs::Bytes(words * 4 / 3));
+        args.insert("encoding", CommandLineArgs::Encoding(Code::Bip39));
+        return add_seed(&matches, args);
+    }
+
     let bytes = matches.value_of("bytes").unwrap();
     match bytes.parse::<usize>() {
         Ok(n) => {
@@ -77,12 +99,19 @@ pub fn parse_cmd_line() -> Result<BTreeMap<&'static str, CommandLineArgs>, Strin
         }
     }
 
-    let encoding = matches.value_of("encoding").unwrap_or("hex");
-    args.insert(
-        "encoding",
-        CommandLineArgs::Encoding(Code::parse(encoding).unwrap()),
-    );
+    let encoding = match matches.value_of("alphabet") {
+        Some(a) => Code::Custom(Alphabet::new(a)?),
+        None => Code::parse(matches.value_of("encoding").unwrap_or("hex")).unwrap(),
+    };
+    args.insert("encoding", CommandLineArgs::Encoding(encoding));
+
+    add_seed(&matches, args)
+}
 
+fn add_seed(
+    matches: &ArgMatches,
+    mut args: BTreeMap<&'static str, CommandLineArgs>,
+) -> Result<BTreeMap<&'static str, CommandLineArgs>, String> {
     if matches.is_present("seed") {
         let temp;
         match matches.value_of("seed") {
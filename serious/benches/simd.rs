@@ -0,0 +1,70 @@
+use base64_url::base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serious::Encoding;
+
+const SIZES: [usize; 3] = [64, 4096, 1 << 20];
+
+fn data(n: usize) -> Vec<u8> {
+    (0..n).map(|i| (i * 31 + 7) as u8).collect()
+}
+
+fn hex(c: &mut Criterion) {
+    let simd = format!("serious/{}", serious::simd_backend().hex);
+    let mut group = c.benchmark_group("hex encode");
+    for n in SIZES {
+        let input = data(n);
+        group.throughput(Throughput::Bytes(n as u64));
+        group.bench_with_input(BenchmarkId::new("hex", n), &input, |b, i| {
+            b.iter(|| hex::encode(i))
+        });
+        group.bench_with_input(BenchmarkId::new(&simd, n), &input, |b, i| {
+            b.iter(|| Encoding::encode(i, Encoding::LowHex).into_string())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("hex decode");
+    for n in SIZES {
+        let input = hex::encode(data(n));
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("hex", n), &input, |b, i| {
+            b.iter(|| hex::decode(i).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new(&simd, n), &input, |b, i| {
+            b.iter(|| Encoding::decode(i, Encoding::LowHex).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn base64(c: &mut Criterion) {
+    let simd = format!("serious/{}", serious::simd_backend().base64);
+    let mut group = c.benchmark_group("base64 encode");
+    for n in SIZES {
+        let input = data(n);
+        group.throughput(Throughput::Bytes(n as u64));
+        group.bench_with_input(BenchmarkId::new("base64", n), &input, |b, i| {
+            b.iter(|| URL_SAFE_NO_PAD.encode(i))
+        });
+        group.bench_with_input(BenchmarkId::new(&simd, n), &input, |b, i| {
+            b.iter(|| Encoding::encode(i, Encoding::Base64).into_string())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("base64 decode");
+    for n in SIZES {
+        let input = URL_SAFE_NO_PAD.encode(data(n));
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("base64", n), &input, |b, i| {
+            b.iter(|| URL_SAFE_NO_PAD.decode(i).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new(&simd, n), &input, |b, i| {
+            b.iter(|| Encoding::decode(i, Encoding::Base64).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, hex, base64);
+criterion_main!(benches);
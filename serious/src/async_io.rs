@@ -0,0 +1,287 @@
+use crate::stream::{invalid, Chunker, CHUNK};
+use crate::Encoding;
+use bytes::{BufMut, BytesMut};
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Encodes or decodes everything read from the inner reader, the async
+/// equivalent of [`crate::Reader`]
+pub struct AsyncReader<R> {
+    inner: R,
+    chunker: Chunker,
+    input: Box<[u8]>,
+    eof: bool,
+}
+
+impl<R: AsyncRead + Unpin> AsyncReader<R> {
+    pub fn encoder(inner: R, encoding: Encoding) -> Self {
+        AsyncReader::new(inner, Chunker::new(encoding, false))
+    }
+
+    pub fn decoder(inner: R, encoding: Encoding) -> Self {
+        AsyncReader::new(inner, Chunker::new(encoding, true))
+    }
+
+    fn new(inner: R, chunker: Chunker) -> Self {
+        AsyncReader {
+            inner,
+            chunker,
+            input: vec![0u8; CHUNK].into_boxed_slice(),
+            eof: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for AsyncReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        while this.chunker.output().is_empty() && !this.eof {
+            let mut input = ReadBuf::new(&mut this.input);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut input))?;
+            let n = input.filled().len();
+            this.eof = n == 0;
+            this.chunker.feed(&this.input[..n])?;
+        }
+        let out = this.chunker.output();
+        let n = out.len().min(buf.remaining());
+        buf.put_slice(&out[..n]);
+        this.chunker.consume(n);
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Encodes or decodes everything written before passing it to the inner
+/// writer, the async equivalent of [`crate::Writer`]. The last partial block
+/// is written on shutdown.
+pub struct AsyncWriter<W> {
+    inner: W,
+    chunker: Chunker,
+    finished: bool,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWriter<W> {
+    pub fn encoder(inner: W, encoding: Encoding) -> Self {
+        AsyncWriter {
+            inner,
+            chunker: Chunker::new(encoding, false),
+            finished: false,
+        }
+    }
+
+    pub fn decoder(inner: W, encoding: Encoding) -> Self {
+        AsyncWriter {
+            inner,
+            chunker: Chunker::new(encoding, true),
+            finished: false,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Write all processed output to the inner writer
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.chunker.output().is_empty() {
+            let n = ready!(Pin::new(&mut self.inner).poll_write(cx, self.chunker.output()))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.chunker.consume(n);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        this.chunker.update(buf)?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.finished {
+            this.chunker.finish()?;
+            this.finished = true;
+        }
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+/// A `tokio_util` codec for records that are encoded one per line, like hex
+/// or base64 lines in a log or a newline delimited socket protocol
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LineCodec {
+    encoding: Encoding,
+    max_length: usize,
+    /// Where to resume searching for a newline in a partial line
+    next_index: usize,
+}
+
+impl LineCodec {
+    pub fn new(encoding: Encoding) -> Self {
+        LineCodec {
+            encoding,
+            max_length: usize::MAX,
+            next_index: 0,
+        }
+    }
+
+    /// Fail on encoded lines longer than `max_length` instead of buffering them
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    fn decode_line(&mut self, line: &[u8]) -> io::Result<Vec<u8>> {
+        self.next_index = 0;
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        Encoding::decode_bytes(line, self.encoding).map_err(invalid)
+    }
+}
+
+impl Decoder for LineCodec {
+    type Item = Vec<u8>;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Vec<u8>>> {
+        match src[self.next_index..].iter().position(|b| *b == b'\n') {
+            Some(i) => {
+                let line = src.split_to(self.next_index + i + 1);
+                self.decode_line(&line).map(Some)
+            }
+            None if src.len() > self.max_length => Err(invalid(format!(
+                "Line is longer than {} bytes",
+                self.max_length
+            ))),
+            None => {
+                self.next_index = src.len();
+                Ok(None)
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> io::Result<Option<Vec<u8>>> {
+        match self.decode(src)? {
+            Some(record) => Ok(Some(record)),
+            None if src.is_empty() => Ok(None),
+            None => {
+                let line = src.split();
+                self.decode_line(&line).map(Some)
+            }
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> Encoder<T> for LineCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> io::Result<()> {
+        let encoded = Encoding::encode(item.as_ref(), self.encoding)
+            .try_into_vec()
+            .map_err(invalid)?;
+        if encoded.contains(&b'\n') {
+            return Err(invalid(format!(
+                "{} output spans lines so it can't be line framed",
+                self.encoding
+            )));
+        }
+        dst.reserve(encoded.len() + 1);
+        dst.put_slice(&encoded);
+        dst.put_u8(b'\n');
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Encoding::*;
+    use rand::RngCore;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn adapters() {
+        let mut buffer = vec![0u8; CHUNK * 2 + 101];
+        rand::thread_rng().fill_bytes(buffer.as_mut_slice());
+        for e in [LowHex, Base64, Base64Url, RadixBytes(8), Base58] {
+            let buffer = match e {
+                Base58 => &buffer[..300],
+                _ => &buffer[..],
+            };
+            let expected = Encoding::encode(buffer, e).into_vec();
+
+            let mut writer = AsyncWriter::encoder(Vec::new(), e);
+            for chunk in buffer.chunks(7) {
+                writer.write_all(chunk).await.unwrap();
+            }
+            writer.shutdown().await.unwrap();
+            assert_eq!(writer.into_inner(), expected, "{}", e);
+
+            let mut decoded = Vec::new();
+            AsyncReader::decoder(expected.as_slice(), e)
+                .read_to_end(&mut decoded)
+                .await
+                .unwrap();
+            assert_eq!(decoded, buffer, "{}", e);
+        }
+
+        let mut decoded = Vec::new();
+        assert!(AsyncReader::decoder(&b"0g"[..], LowHex)
+            .read_to_end(&mut decoded)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn lines() {
+        let mut codec = LineCodec::new(Base64);
+        let mut buf = BytesMut::new();
+        codec.encode(b"hello", &mut buf).unwrap();
+        codec.encode(b"", &mut buf).unwrap();
+        codec.encode(b"world", &mut buf).unwrap();
+        assert_eq!(&buf[..], b"aGVsbG8\n\nd29ybGQ\n");
+
+        let mut src = BytesMut::from(&b"aGVsbG8\r\n\nd29y"[..]);
+        assert_eq!(codec.decode(&mut src).unwrap().unwrap(), b"hello");
+        assert_eq!(codec.decode(&mut src).unwrap().unwrap(), b"");
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        src.extend_from_slice(b"bGQ");
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        assert_eq!(codec.decode_eof(&mut src).unwrap().unwrap(), b"world");
+        assert!(codec.decode_eof(&mut src).unwrap().is_none());
+
+        let mut codec = LineCodec::new(LowHex).with_max_length(4);
+        assert!(codec.decode(&mut BytesMut::from(&b"00ff00"[..])).is_err());
+        assert!(codec.decode(&mut BytesMut::from(&b"zz\n"[..])).is_err());
+        assert!(LineCodec::new(Uuencode)
+            .encode(b"hello", &mut BytesMut::new())
+            .is_err());
+    }
+}
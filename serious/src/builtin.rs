@@ -0,0 +1,406 @@
+use crate::Encoding::{self, *};
+use crate::{
+    big_decode, datauri, eip55, escape, legacy, radix_width, simd, spoken, FileHeader, MediaType,
+    MnemonicLanguage,
+};
+use base64_url::base64;
+use num_bigint::BigUint;
+
+const BASE62: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+/// Longest word in the PGP word list
+const PGP_WORD_LENGTH: usize = 11;
+
+/// How an encoding turns bytes into its encoded form and back
+pub(crate) enum Coder {
+    /// The encoded form is printable text
+    Text {
+        encode: fn(&[u8]) -> Result<String, String>,
+        decode: fn(&str) -> Result<Vec<u8>, String>,
+    },
+    /// The encoded form is raw bytes like blob and yEnc
+    Bytes {
+        encode: fn(&[u8]) -> Vec<u8>,
+        decode: fn(&[u8]) -> Result<Vec<u8>, String>,
+    },
+}
+
+/// An encoding without parameters. Its row in [`BUILTINS`] is all it needs to
+/// be parsed, listed, encoded, decoded and sized.
+pub(crate) struct Builtin {
+    pub(crate) encoding: Encoding,
+    /// The name used by `Display` and shown by the command line tools
+    pub(crate) name: &'static str,
+    pub(crate) aliases: &'static [&'static str],
+    pub(crate) coder: Coder,
+    /// See [`Encoding::encoded_len`]
+    pub(crate) encoded_len: fn(usize) -> Option<usize>,
+    /// See [`Encoding::decoded_len_estimate`]
+    pub(crate) decoded_len: fn(usize) -> Option<usize>,
+}
+
+impl Builtin {
+    pub(crate) fn find(encoding: Encoding) -> Option<&'static Builtin> {
+        BUILTINS.iter().find(|b| b.encoding == encoding)
+    }
+}
+
+fn big_encode(data: &[u8], r: u32) -> Result<String, String> {
+    Ok(BigUint::from_bytes_be(data).to_str_radix(r))
+}
+
+fn base58_encode(data: &[u8], alphabet: &'static bs58::Alphabet) -> Result<String, String> {
+    Ok(bs58::encode(data).with_alphabet(alphabet).into_string())
+}
+
+fn base58_decode(s: &str, alphabet: &'static bs58::Alphabet) -> Result<Vec<u8>, String> {
+    bs58::decode(s)
+        .with_alphabet(alphabet)
+        .into_vec()
+        .map_err(|e| e.to_string())
+}
+
+/// A big integer never has more digits than writing each byte separately
+fn big_len(n: usize, r: u32) -> Option<usize> {
+    n.checked_mul(radix_width(r)).map(|l| l.max(1))
+}
+
+/// Decoding escapes and radix encodings never produces more bytes than characters
+fn at_most(n: usize) -> Option<usize> {
+    Some(n)
+}
+
+/// Every encoding without parameters in the order listed by [`Encoding::values`]
+pub(crate) static BUILTINS: &[Builtin] = &[
+    Builtin {
+        encoding: Blob,
+        name: "blob",
+        aliases: &[],
+        coder: Coder::Bytes {
+            encode: <[u8]>::to_vec,
+            decode: |d| Ok(d.to_vec()),
+        },
+        encoded_len: Some,
+        decoded_len: Some,
+    },
+    Builtin {
+        encoding: Binary,
+        name: "binary",
+        aliases: &["bin"],
+        coder: Coder::Text {
+            encode: |d| big_encode(d, 2),
+            decode: |s| big_decode(s, 2, Binary),
+        },
+        encoded_len: |n| big_len(n, 2),
+        decoded_len: |n| Some(n.div_ceil(8)),
+    },
+    Builtin {
+        encoding: Base10,
+        name: "base10",
+        aliases: &["bs10"],
+        coder: Coder::Text {
+            encode: |d| big_encode(d, 10),
+            decode: |s| big_decode(s, 10, Base10),
+        },
+        encoded_len: |n| big_len(n, 10),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: Radix(8),
+        name: "octal",
+        aliases: &["oct"],
+        coder: Coder::Text {
+            encode: |d| big_encode(d, 8),
+            decode: |s| big_decode(s, 8, Radix(8)),
+        },
+        encoded_len: |n| big_len(n, 8),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: Radix(36),
+        name: "base36",
+        aliases: &["bs36"],
+        coder: Coder::Text {
+            encode: |d| big_encode(d, 36),
+            decode: |s| big_decode(s, 36, Radix(36)),
+        },
+        encoded_len: |n| big_len(n, 36),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: Base58,
+        name: "base58",
+        aliases: &["bs58"],
+        coder: Coder::Text {
+            encode: |d| base58_encode(d, bs58::Alphabet::BITCOIN),
+            decode: |s| base58_decode(s, bs58::Alphabet::BITCOIN),
+        },
+        encoded_len: |n| n.checked_mul(radix_width(58)),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: Base62,
+        name: "base62",
+        aliases: &["bs62"],
+        coder: Coder::Text {
+            encode: |d| Ok(base_x::encode(BASE62, d)),
+            decode: |s| base_x::decode(BASE62, s).map_err(|e| e.to_string()),
+        },
+        encoded_len: |n| n.checked_mul(radix_width(62)),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: Base64,
+        name: "base64",
+        aliases: &["bs64"],
+        coder: Coder::Text {
+            encode: |d| Ok(simd::base64_encode(d, true)),
+            decode: |s| simd::base64_decode(s.as_bytes(), true),
+        },
+        encoded_len: |n| base64::encoded_len(n, false),
+        decoded_len: |n| Some(base64::decoded_len_estimate(n)),
+    },
+    Builtin {
+        encoding: Base64Url,
+        name: "base64url",
+        aliases: &["bs64u"],
+        coder: Coder::Text {
+            encode: |d| Ok(simd::base64_encode(d, true)),
+            decode: |s| simd::base64_decode(s.as_bytes(), true),
+        },
+        encoded_len: |n| base64::encoded_len(n, false),
+        decoded_len: |n| Some(base64::decoded_len_estimate(n)),
+    },
+    Builtin {
+        encoding: BitCoin,
+        name: "bitcoin",
+        aliases: &["btc"],
+        coder: Coder::Text {
+            encode: |d| base58_encode(d, bs58::Alphabet::BITCOIN),
+            decode: |s| base58_decode(s, bs58::Alphabet::BITCOIN),
+        },
+        encoded_len: |n| n.checked_mul(radix_width(58)),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: Flickr,
+        name: "flickr",
+        aliases: &["fkr"],
+        coder: Coder::Text {
+            encode: |d| base58_encode(d, bs58::Alphabet::FLICKR),
+            decode: |s| base58_decode(s, bs58::Alphabet::FLICKR),
+        },
+        encoded_len: |n| n.checked_mul(radix_width(58)),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: LowHex,
+        name: "hex",
+        aliases: &["lowhex"],
+        coder: Coder::Text {
+            encode: |d| Ok(simd::hex_encode(d, false)),
+            decode: |s| simd::hex_decode(s.as_bytes()),
+        },
+        encoded_len: |n| n.checked_mul(2),
+        decoded_len: |n| Some(n / 2),
+    },
+    Builtin {
+        encoding: Monero,
+        name: "monero",
+        aliases: &["xmr"],
+        coder: Coder::Text {
+            encode: |d| base58_encode(d, bs58::Alphabet::MONERO),
+            decode: |s| base58_decode(s, bs58::Alphabet::MONERO),
+        },
+        encoded_len: |n| n.checked_mul(radix_width(58)),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: Ripple,
+        name: "ripple",
+        aliases: &["xrp"],
+        coder: Coder::Text {
+            encode: |d| base58_encode(d, bs58::Alphabet::RIPPLE),
+            decode: |s| base58_decode(s, bs58::Alphabet::RIPPLE),
+        },
+        encoded_len: |n| n.checked_mul(radix_width(58)),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: UpHex,
+        name: "uphex",
+        aliases: &["uhx"],
+        coder: Coder::Text {
+            encode: |d| Ok(simd::hex_encode(d, true)),
+            decode: |s| simd::hex_decode(s.as_bytes()),
+        },
+        encoded_len: |n| n.checked_mul(2),
+        decoded_len: |n| Some(n / 2),
+    },
+    Builtin {
+        encoding: Eip55Hex,
+        name: "eip55",
+        aliases: &["eip55hex"],
+        coder: Coder::Text {
+            encode: |d| eip55::eip55_encode(d, None),
+            decode: |s| eip55::eip55_decode(s, None),
+        },
+        encoded_len: |n| n.checked_mul(2)?.checked_add(2),
+        decoded_len: |n| Some(n / 2),
+    },
+    Builtin {
+        encoding: Percent,
+        name: "percent",
+        aliases: &["pct"],
+        coder: Coder::Text {
+            encode: |d| Ok(escape::percent_encode(d, false)),
+            decode: |s| escape::percent_decode(s, false),
+        },
+        encoded_len: |n| n.checked_mul(3),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: Form,
+        name: "form",
+        aliases: &["urlencoded"],
+        coder: Coder::Text {
+            encode: |d| Ok(escape::percent_encode(d, true)),
+            decode: |s| escape::percent_decode(s, true),
+        },
+        encoded_len: |n| n.checked_mul(3),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: QuotedPrintable,
+        name: "quoted-printable",
+        aliases: &["qp"],
+        coder: Coder::Text {
+            encode: |d| Ok(escape::qp_encode(d)),
+            decode: escape::qp_decode,
+        },
+        // Every escape is 3 characters and a soft break is added at least every 73
+        encoded_len: |n| n.checked_mul(3).map(|l| l + l / 73 * 3),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: Json,
+        name: "json",
+        aliases: &[],
+        coder: Coder::Text {
+            encode: escape::json_encode,
+            decode: escape::json_decode,
+        },
+        encoded_len: |n| n.checked_mul(6),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: CEscape,
+        name: "cescape",
+        aliases: &["cesc"],
+        coder: Coder::Text {
+            encode: |d| Ok(escape::c_encode(d)),
+            decode: escape::c_decode,
+        },
+        encoded_len: |n| n.checked_mul(4),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: Uuencode,
+        name: "uuencode",
+        aliases: &["uu"],
+        coder: Coder::Text {
+            encode: |d| Ok(legacy::uu_encode(d, &FileHeader::default())),
+            decode: |s| legacy::uu_decode(s.as_bytes()).map(|(_, d)| d),
+        },
+        encoded_len: |n| legacy::uu_encoded_len(n, &FileHeader::default()),
+        decoded_len: |n| Some(n / 4 * 3),
+    },
+    Builtin {
+        encoding: YEnc,
+        name: "yenc",
+        aliases: &[],
+        coder: Coder::Bytes {
+            encode: |d| legacy::yenc_encode(d, &FileHeader::default()),
+            decode: |d| legacy::yenc_decode(d).map(|(_, d)| d),
+        },
+        encoded_len: |n| legacy::yenc_encoded_len(n, &FileHeader::default()),
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: BinHex,
+        name: "binhex",
+        aliases: &["hqx"],
+        coder: Coder::Text {
+            encode: |d| legacy::binhex_encode(d, &FileHeader::default()),
+            decode: |s| legacy::binhex_decode(s.as_bytes()).map(|(_, d)| d),
+        },
+        encoded_len: |n| legacy::binhex_encoded_len(n, &FileHeader::default()),
+        // Run length encoding can expand without limit
+        decoded_len: |_| None,
+    },
+    Builtin {
+        encoding: DataUri,
+        name: "datauri",
+        aliases: &["duri"],
+        coder: Coder::Text {
+            encode: |d| Ok(datauri::data_uri_encode(d, &MediaType::sniff(d))),
+            decode: |s| datauri::data_uri_decode(s).map(|(_, d)| d),
+        },
+        encoded_len: datauri::data_uri_encoded_len,
+        decoded_len: at_most,
+    },
+    Builtin {
+        encoding: Bip39,
+        name: "bip39",
+        aliases: &["mnemonic"],
+        coder: Coder::Text {
+            encode: |d| Encoding::encode_mnemonic(d, MnemonicLanguage::English),
+            // The language is detected from the words among the enabled word lists
+            decode: |s| {
+                bip39::Mnemonic::parse(s)
+                    .map(|m| m.to_entropy())
+                    .map_err(|e| e.to_string())
+            },
+        },
+        // 3 words for every 4 bytes of entropy separated by spaces
+        encoded_len: |n| match n {
+            16..=32 if n.is_multiple_of(4) => {
+                let longest = MnemonicLanguage::English
+                    .word_list()
+                    .iter()
+                    .map(|w| w.len())
+                    .max()?;
+                Some(n / 4 * 3 * (longest + 1) - 1)
+            }
+            _ => None,
+        },
+        decoded_len: |_| Some(32),
+    },
+    Builtin {
+        encoding: PgpWords,
+        name: "pgpwords",
+        aliases: &["pgp"],
+        coder: Coder::Text {
+            encode: |d| Ok(spoken::pgp_encode(d)),
+            decode: spoken::pgp_decode,
+        },
+        encoded_len: |n| {
+            n.checked_mul(PGP_WORD_LENGTH + 1)
+                .map(|l| l.saturating_sub(1))
+        },
+        decoded_len: |n| Some(n / 5 + 1),
+    },
+    Builtin {
+        encoding: Proquint,
+        name: "proquint",
+        aliases: &["pq"],
+        coder: Coder::Text {
+            encode: spoken::proquint_encode,
+            decode: spoken::proquint_decode,
+        },
+        encoded_len: |n| match n.is_multiple_of(2) {
+            true => Some((n / 2 * 6).saturating_sub(1)),
+            false => None,
+        },
+        decoded_len: |n| Some((n + 1) / 6 * 2),
+    },
+];
@@ -0,0 +1,282 @@
+use std::fmt::Display;
+
+/// Windows-1252 code points for 0x80 to 0x9F, the rest match ISO-8859-1
+const WINDOWS_1252: [Option<char>; 32] = [
+    Some('\u{20AC}'),
+    None,
+    Some('\u{201A}'),
+    Some('\u{0192}'),
+    Some('\u{201E}'),
+    Some('\u{2026}'),
+    Some('\u{2020}'),
+    Some('\u{2021}'),
+    Some('\u{02C6}'),
+    Some('\u{2030}'),
+    Some('\u{0160}'),
+    Some('\u{2039}'),
+    Some('\u{0152}'),
+    None,
+    Some('\u{017D}'),
+    None,
+    None,
+    Some('\u{2018}'),
+    Some('\u{2019}'),
+    Some('\u{201C}'),
+    Some('\u{201D}'),
+    Some('\u{2022}'),
+    Some('\u{2013}'),
+    Some('\u{2014}'),
+    Some('\u{02DC}'),
+    Some('\u{2122}'),
+    Some('\u{0161}'),
+    Some('\u{203A}'),
+    Some('\u{0153}'),
+    None,
+    Some('\u{017E}'),
+    Some('\u{0178}'),
+];
+
+/// Character sets for converting text to and from bytes
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Charset {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+    /// ISO-8859-1
+    Latin1,
+    Windows1252,
+}
+
+use Charset::*;
+
+impl Charset {
+    pub fn parse<I: AsRef<str>>(s: I) -> Result<Self, String> {
+        let s = s.as_ref();
+        match s.to_lowercase().as_str() {
+            "utf8" | "utf-8" => Ok(Utf8),
+            "utf16le" | "utf-16le" => Ok(Utf16Le),
+            "utf16be" | "utf-16be" => Ok(Utf16Be),
+            "utf32le" | "utf-32le" => Ok(Utf32Le),
+            "utf32be" | "utf-32be" => Ok(Utf32Be),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(Latin1),
+            "cp1252" | "windows-1252" => Ok(Windows1252),
+            _ => Err(format!("Unknown character set: {}", s)),
+        }
+    }
+
+    /// The byte order mark, empty for character sets without one
+    pub fn bom(&self) -> &'static [u8] {
+        match *self {
+            Utf8 => &[0xEF, 0xBB, 0xBF],
+            Utf16Le => &[0xFF, 0xFE],
+            Utf16Be => &[0xFE, 0xFF],
+            Utf32Le => &[0xFF, 0xFE, 0x00, 0x00],
+            Utf32Be => &[0x00, 0x00, 0xFE, 0xFF],
+            Latin1 | Windows1252 => &[],
+        }
+    }
+
+    /// Find the character set from a byte order mark
+    pub fn detect<T: AsRef<[u8]>>(data: T) -> Option<Self> {
+        let data = data.as_ref();
+        // UTF-32LE must be checked before its UTF-16LE prefix
+        [Utf32Le, Utf32Be, Utf8, Utf16Le, Utf16Be]
+            .into_iter()
+            .find(|c| data.starts_with(c.bom()))
+    }
+
+    /// Convert bytes in a character set to text, removing a byte order mark if present
+    pub fn decode<T: AsRef<[u8]>>(data: T, src: Self) -> Result<String, String> {
+        let data = data.as_ref();
+        let data = data.strip_prefix(src.bom()).unwrap_or(data);
+        let invalid = |i: usize| format!("Invalid {} sequence at byte {}", src, i);
+        match src {
+            Utf8 => std::str::from_utf8(data)
+                .map(|s| s.to_string())
+                .map_err(|e| invalid(e.valid_up_to())),
+            Utf16Le | Utf16Be => {
+                if data.len() % 2 != 0 {
+                    return Err(invalid(data.len() - 1));
+                }
+                let units = data.chunks(2).map(|c| match src {
+                    Utf16Le => u16::from_le_bytes([c[0], c[1]]),
+                    _ => u16::from_be_bytes([c[0], c[1]]),
+                });
+                let mut out = String::with_capacity(data.len() / 2);
+                for (i, c) in char::decode_utf16(units).enumerate() {
+                    out.push(c.map_err(|_| invalid(i * 2))?);
+                }
+                Ok(out)
+            }
+            Utf32Le | Utf32Be => {
+                if data.len() % 4 != 0 {
+                    return Err(invalid(data.len() - data.len() % 4));
+                }
+                data.chunks(4)
+                    .enumerate()
+                    .map(|(i, c)| {
+                        let c = [c[0], c[1], c[2], c[3]];
+                        let c = match src {
+                            Utf32Le => u32::from_le_bytes(c),
+                            _ => u32::from_be_bytes(c),
+                        };
+                        char::from_u32(c).ok_or_else(|| invalid(i * 4))
+                    })
+                    .collect()
+            }
+            Latin1 => Ok(data.iter().map(|b| char::from(*b)).collect()),
+            Windows1252 => data
+                .iter()
+                .enumerate()
+                .map(|(i, b)| match b {
+                    0x80..=0x9F => WINDOWS_1252[usize::from(b - 0x80)].ok_or_else(|| invalid(i)),
+                    b => Ok(char::from(*b)),
+                })
+                .collect(),
+        }
+    }
+
+    /// Convert text to bytes in a character set, optionally starting with a byte order mark
+    pub fn encode<T: AsRef<str>>(s: T, tgt: Self, bom: bool) -> Result<Vec<u8>, String> {
+        let s = s.as_ref();
+        let mut out = Vec::with_capacity(s.len() * 4);
+        if bom {
+            out.extend_from_slice(tgt.bom());
+        }
+        let unmappable = |c: char| format!("'{}' can't be represented in {}", c, tgt);
+        match tgt {
+            Utf8 => out.extend_from_slice(s.as_bytes()),
+            Utf16Le => s
+                .encode_utf16()
+                .for_each(|u| out.extend_from_slice(&u.to_le_bytes())),
+            Utf16Be => s
+                .encode_utf16()
+                .for_each(|u| out.extend_from_slice(&u.to_be_bytes())),
+            Utf32Le => s
+                .chars()
+                .for_each(|c| out.extend_from_slice(&u32::from(c).to_le_bytes())),
+            Utf32Be => s
+                .chars()
+                .for_each(|c| out.extend_from_slice(&u32::from(c).to_be_bytes())),
+            Latin1 => {
+                for c in s.chars() {
+                    out.push(u8::try_from(c).map_err(|_| unmappable(c))?);
+                }
+            }
+            Windows1252 => {
+                for c in s.chars() {
+                    let b = match u8::try_from(c) {
+                        Ok(b) if !(0x80..=0x9F).contains(&b) => b,
+                        _ => WINDOWS_1252
+                            .iter()
+                            .position(|w| *w == Some(c))
+                            .map(|i| 0x80 + i as u8)
+                            .ok_or_else(|| unmappable(c))?,
+                    };
+                    out.push(b);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn transcode<T: AsRef<[u8]>>(
+        data: T,
+        src: Self,
+        tgt: Self,
+        bom: bool,
+    ) -> Result<Vec<u8>, String> {
+        let s = Charset::decode(data, src)?;
+        Charset::encode(s, tgt, bom)
+    }
+
+    pub fn values() -> Vec<Self> {
+        vec![
+            Utf8,
+            Utf16Le,
+            Utf16Be,
+            Utf32Le,
+            Utf32Be,
+            Latin1,
+            Windows1252,
+        ]
+    }
+}
+
+impl Display for Charset {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Utf8 => write!(f, "utf-8"),
+            Utf16Le => write!(f, "utf-16le"),
+            Utf16Be => write!(f, "utf-16be"),
+            Utf32Le => write!(f, "utf-32le"),
+            Utf32Be => write!(f, "utf-32be"),
+            Latin1 => write!(f, "iso-8859-1"),
+            Windows1252 => write!(f, "windows-1252"),
+        }
+    }
+}
+
+impl From<&str> for Charset {
+    fn from(s: &str) -> Self {
+        Charset::parse(s).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transcode() {
+        let text = "Caf\u{e9} \u{201C}quoted\u{201D} \u{20AC}5";
+        for v in Charset::values() {
+            if v == Latin1 {
+                assert!(Charset::encode(text, v, false).is_err());
+                continue;
+            }
+            for bom in [false, true] {
+                let res = Charset::encode(text, v, bom).unwrap();
+                assert_eq!(bom && v != Windows1252, Charset::detect(&res) == Some(v));
+                assert_eq!(text, Charset::decode(&res, v).unwrap());
+            }
+        }
+        let emoji = "\u{1F600}";
+        for v in [Utf8, Utf16Le, Utf16Be, Utf32Le, Utf32Be] {
+            let res = Charset::encode(emoji, v, false).unwrap();
+            assert_eq!(emoji, Charset::decode(&res, v).unwrap());
+        }
+        assert_eq!(
+            Charset::transcode(b"\xFF\xFEh\x00i\x00", Utf16Le, Utf8, false).unwrap(),
+            b"hi"
+        );
+        assert_eq!(
+            Charset::transcode(b"\x80\xE9", Windows1252, Latin1, false).unwrap_err(),
+            "'\u{20AC}' can't be represented in iso-8859-1"
+        );
+        assert_eq!(
+            Charset::decode(b"\x80\xE9", Latin1).unwrap(),
+            "\u{80}\u{e9}"
+        );
+    }
+
+    #[test]
+    fn strict() {
+        assert!(Charset::decode(b"\xC3", Utf8).is_err());
+        assert!(Charset::decode(b"h\x00i", Utf16Le).is_err());
+        assert!(Charset::decode(b"\x00\xD8\x41\x00", Utf16Le).is_err());
+        assert!(Charset::decode(b"\x00\xD8\x00\x00", Utf32Le).is_err());
+        assert!(Charset::decode(b"\x81", Windows1252).is_err());
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(Some(Utf32Le), Charset::detect(b"\xFF\xFE\x00\x00"));
+        assert_eq!(Some(Utf16Le), Charset::detect(b"\xFF\xFEh\x00"));
+        assert_eq!(Some(Utf16Be), Charset::detect(b"\xFE\xFF\x00h"));
+        assert_eq!(Some(Utf8), Charset::detect(b"\xEF\xBB\xBFh"));
+        assert_eq!(None, Charset::detect(b"hello"));
+    }
+}
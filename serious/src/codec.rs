@@ -0,0 +1,151 @@
+use crate::{pipeline, Encoding};
+use std::sync::{Arc, RwLock};
+
+/// An encoding that can be added to the registry with [`register`].
+///
+/// Once registered it's parsed by name like the built in encodings, listed in
+/// [`Encoding::values`] and usable anywhere an [`Encoding`] is accepted
+/// including pipelines and the command line tools.
+pub trait Codec: Send + Sync {
+    /// The name shown in help and used by `Display`
+    fn name(&self) -> &str;
+
+    /// Other names accepted when parsing
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    fn encode(&self, data: &[u8]) -> Result<Vec<u8>, String>;
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>, String>;
+
+    /// False when the encoded form is raw bytes instead of printable text
+    fn is_text(&self) -> bool {
+        true
+    }
+}
+
+/// Refers to a codec in the registry. Only [`register`] hands these out so
+/// every id refers to a registered codec.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct CodecId(usize);
+
+pub(crate) struct Entry {
+    pub(crate) name: &'static str,
+    pub(crate) aliases: &'static [&'static str],
+    pub(crate) codec: Arc<dyn Codec>,
+}
+
+static REGISTRY: RwLock<Vec<Entry>> = RwLock::new(Vec::new());
+
+/// Prefixes of the encodings that take a parameter
+const RESERVED_PREFIXES: [&str; 3] = ["custom", "radix", "eip1191"];
+
+/// Add a codec to the registry returning the [`Encoding`] that refers to it.
+/// Fails if its name or an alias is already taken, or couldn't be parsed
+/// because it's empty, contains `:`, `|` or whitespace, is a pipeline keyword
+/// like `gzip` or `reverse`, or starts with `custom`, `radix` or `eip1191`.
+///
+/// A codec and its names stay in memory for the rest of the program, so
+/// register each one once at startup rather than every time it's used.
+pub fn register<C: Codec + 'static>(codec: C) -> Result<Encoding, String> {
+    let mut registry = REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+    let names = std::iter::once(codec.name()).chain(codec.aliases().iter().copied());
+    for n in names {
+        if n.is_empty() {
+            return Err("Encoding names can't be empty".to_string());
+        }
+        if n.contains(|c: char| c == ':' || c == '|' || c.is_whitespace()) {
+            return Err(format!(
+                "Encoding name '{}' can't contain ':', '|' or whitespace",
+                n
+            ));
+        }
+        if let Some(p) = RESERVED_PREFIXES.iter().find(|p| n.starts_with(*p)) {
+            return Err(format!(
+                "Encoding name '{}' can't start with the reserved prefix '{}'",
+                n, p
+            ));
+        }
+        if pipeline::is_keyword(n) {
+            return Err(format!("Encoding name '{}' is a pipeline keyword", n));
+        }
+        let taken = Encoding::builtins()
+            .iter()
+            .any(|e| e.name() == n || e.aliases().contains(&n))
+            || registry
+                .iter()
+                .any(|e| e.name == n || e.aliases.contains(&n));
+        if taken {
+            return Err(format!("Encoding name '{}' is already taken", n));
+        }
+    }
+    // Registered codecs live for the rest of the program so their names can too
+    let name: &'static str = Box::leak(codec.name().to_string().into_boxed_str());
+    let aliases = codec
+        .aliases()
+        .iter()
+        .map(|a| &*Box::leak(a.to_string().into_boxed_str()))
+        .collect::<Vec<&'static str>>();
+    registry.push(Entry {
+        name,
+        aliases: Box::leak(aliases.into_boxed_slice()),
+        codec: Arc::new(codec),
+    });
+    Ok(Encoding::Registered(CodecId(registry.len() - 1)))
+}
+
+/// Every registered codec in the order they were added
+pub(crate) fn registered() -> Vec<Encoding> {
+    let count = REGISTRY.read().unwrap_or_else(|e| e.into_inner()).len();
+    (0..count)
+        .map(|i| Encoding::Registered(CodecId(i)))
+        .collect()
+}
+
+pub(crate) fn with_entry<R, F: FnOnce(&Entry) -> R>(id: CodecId, f: F) -> R {
+    let registry = REGISTRY.read().unwrap_or_else(|e| e.into_inner());
+    // Entries are never removed so every id handed out is in range
+    f(&registry[id.0])
+}
+
+pub(crate) fn codec(id: CodecId) -> Arc<dyn Codec> {
+    with_entry(id, |e| e.codec.clone())
+}
+
+impl Codec for Encoding {
+    fn name(&self) -> &str {
+        Encoding::name(self)
+    }
+
+    fn aliases(&self) -> &[&str] {
+        Encoding::aliases(self)
+    }
+
+    fn encode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        Encoding::encode(data, *self).try_into_vec()
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        Encoding::decode_bytes(data, *self)
+    }
+
+    fn is_text(&self) -> bool {
+        Encoding::is_text(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_names() {
+        for e in Encoding::builtins() {
+            assert_eq!(Encoding::parse(e.name()).unwrap(), e);
+            for a in e.aliases() {
+                assert_eq!(Encoding::parse(a).unwrap(), e);
+            }
+        }
+    }
+}
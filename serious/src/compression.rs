@@ -0,0 +1,156 @@
+use flate2::read::{
+    DeflateDecoder, DeflateEncoder, GzDecoder, GzEncoder, ZlibDecoder, ZlibEncoder,
+};
+use std::fmt::Display;
+use std::io::Read;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+const XZ_MAGIC: [u8; 6] = [0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Compression {
+    Gzip,
+    Zlib,
+    /// Raw DEFLATE without a header as used by JWT `zip: DEF`
+    Deflate,
+    Zstd,
+    Brotli,
+    Xz,
+}
+
+use Compression::*;
+
+impl Compression {
+    pub fn parse<I: AsRef<str>>(s: I) -> Result<Self, String> {
+        let s = s.as_ref();
+        match s {
+            "gz" | "gzip" => Ok(Gzip),
+            "zlib" => Ok(Zlib),
+            "deflate" => Ok(Deflate),
+            "zst" | "zstd" => Ok(Zstd),
+            "br" | "brotli" => Ok(Brotli),
+            "xz" => Ok(Xz),
+            _ => Err(format!("Unknown compression: {}", s)),
+        }
+    }
+
+    /// Guess the compression from the magic bytes at the start of the data.
+    /// Raw DEFLATE and Brotli have no header so they are never detected.
+    pub fn detect<T: AsRef<[u8]>>(data: T) -> Option<Self> {
+        let data = data.as_ref();
+        if data.starts_with(&GZIP_MAGIC) {
+            Some(Gzip)
+        } else if data.starts_with(&ZSTD_MAGIC) {
+            Some(Zstd)
+        } else if data.starts_with(&XZ_MAGIC) {
+            Some(Xz)
+        } else if data.len() >= 2
+            && data[0] & 0x0f == 8
+            && data[0] >> 4 <= 7
+            && u16::from_be_bytes([data[0], data[1]]) % 31 == 0
+        {
+            Some(Zlib)
+        } else {
+            None
+        }
+    }
+
+    pub fn compress<T: AsRef<[u8]>>(data: T, alg: Self) -> Result<Vec<u8>, String> {
+        let data = data.as_ref();
+        let mut out = Vec::new();
+        let res = match alg {
+            Gzip => GzEncoder::new(data, flate2::Compression::default()).read_to_end(&mut out),
+            Zlib => ZlibEncoder::new(data, flate2::Compression::default()).read_to_end(&mut out),
+            Deflate => {
+                DeflateEncoder::new(data, flate2::Compression::default()).read_to_end(&mut out)
+            }
+            Zstd => {
+                zstd::stream::read::Encoder::new(data, 0).and_then(|mut e| e.read_to_end(&mut out))
+            }
+            Brotli => brotli::CompressorReader::new(data, 4096, 11, 22).read_to_end(&mut out),
+            Xz => xz2::read::XzEncoder::new(data, 6).read_to_end(&mut out),
+        };
+        res.map_err(|e| format!("Unable to compress {}: {}", alg, e))?;
+        Ok(out)
+    }
+
+    pub fn decompress<T: AsRef<[u8]>>(data: T, alg: Self) -> Result<Vec<u8>, String> {
+        let data = data.as_ref();
+        let mut out = Vec::new();
+        let res = match alg {
+            Gzip => GzDecoder::new(data).read_to_end(&mut out),
+            Zlib => ZlibDecoder::new(data).read_to_end(&mut out),
+            Deflate => DeflateDecoder::new(data).read_to_end(&mut out),
+            Zstd => {
+                zstd::stream::read::Decoder::new(data).and_then(|mut d| d.read_to_end(&mut out))
+            }
+            Brotli => brotli::Decompressor::new(data, 4096).read_to_end(&mut out),
+            Xz => xz2::read::XzDecoder::new(data).read_to_end(&mut out),
+        };
+        res.map_err(|e| format!("Unable to decompress {}: {}", alg, e))?;
+        Ok(out)
+    }
+
+    /// Decompress using the detected compression, treating data without
+    /// a recognizable header as raw DEFLATE
+    pub fn inflate<T: AsRef<[u8]>>(data: T) -> Result<Vec<u8>, String> {
+        let alg = Compression::detect(&data).unwrap_or(Deflate);
+        Compression::decompress(data, alg)
+    }
+
+    pub fn values() -> Vec<Self> {
+        vec![Gzip, Zlib, Deflate, Zstd, Brotli, Xz]
+    }
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Gzip => write!(f, "gzip"),
+            Zlib => write!(f, "zlib"),
+            Deflate => write!(f, "deflate"),
+            Zstd => write!(f, "zstd"),
+            Brotli => write!(f, "brotli"),
+            Xz => write!(f, "xz"),
+        }
+    }
+}
+
+impl From<&str> for Compression {
+    fn from(s: &str) -> Self {
+        Compression::parse(s).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn compress_decompress() {
+        let mut rng = rand::thread_rng();
+        let mut buffer = vec![0u8; 1024];
+        rng.fill_bytes(buffer.as_mut_slice());
+
+        for v in Compression::values() {
+            let res = Compression::compress(&buffer, v).unwrap();
+            assert_eq!(buffer, Compression::decompress(&res, v).unwrap());
+            if v != Brotli {
+                assert_eq!(buffer, Compression::inflate(&res).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn detect() {
+        let buffer = b"Serious compression";
+        for v in [Gzip, Zlib, Zstd, Xz] {
+            let res = Compression::compress(buffer, v).unwrap();
+            assert_eq!(Some(v), Compression::detect(res));
+        }
+        assert_eq!(None, Compression::detect(buffer));
+        assert_eq!(None, Compression::detect([]));
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+static INTERNED: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// The symbols of a custom radix encoding like `0123456789abcdefghijklmnopqrstuvwxyz`
+/// for base36. The first symbol is zero.
+///
+/// Alphabets are interned so [`crate::Encoding`] stays `Copy`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Alphabet(&'static str);
+
+impl Alphabet {
+    /// Base36 with lowercase letters
+    pub const BASE36: Alphabet = Alphabet("0123456789abcdefghijklmnopqrstuvwxyz");
+    /// Base62 with lowercase before uppercase as used by some URL shorteners
+    pub const BASE62_LOWER: Alphabet =
+        Alphabet("0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ");
+
+    /// Checks there are at least two symbols and none are repeated
+    pub fn new<S: AsRef<str>>(symbols: S) -> Result<Self, String> {
+        let symbols = symbols.as_ref();
+        let mut seen = HashSet::new();
+        for c in symbols.chars() {
+            if !seen.insert(c) {
+                return Err(format!("Duplicate symbol '{}' in alphabet", c));
+            }
+        }
+        if seen.len() < 2 {
+            return Err("An alphabet needs at least 2 symbols".to_string());
+        }
+        let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+        match interned.iter().find(|s| **s == symbols) {
+            Some(s) => Ok(Alphabet(s)),
+            None => {
+                let s: &'static str = Box::leak(symbols.to_string().into_boxed_str());
+                interned.push(s);
+                Ok(Alphabet(s))
+            }
+        }
+    }
+
+    pub fn symbols(&self) -> &'static str {
+        self.0
+    }
+
+    /// The radix of the encoding
+    pub fn len(&self) -> usize {
+        self.0.chars().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn encode(&self, data: &[u8]) -> String {
+        base_x::encode(self.0, data)
+    }
+
+    pub(crate) fn decode(&self, s: &str) -> Result<Vec<u8>, String> {
+        base_x::decode(self.0, s).map_err(|_| format!("Invalid symbol for alphabet {}", self.0))
+    }
+}
+
+impl Display for Alphabet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alphabet() {
+        assert_eq!(
+            Alphabet::new(Alphabet::BASE36.symbols()).unwrap(),
+            Alphabet::BASE36
+        );
+        assert_eq!(Alphabet::BASE36.len(), 36);
+        assert_eq!(Alphabet::BASE36.encode(&[0xff, 0xff]), "1ekf");
+        assert_eq!(Alphabet::BASE36.decode("1ekf").unwrap(), [0xff, 0xff]);
+        assert_eq!(Alphabet::BASE62_LOWER.encode(&[0, 62]), "010");
+        assert!(Alphabet::BASE36.decode("1ekF").is_err());
+
+        let a = Alphabet::new("01").unwrap();
+        assert_eq!(a, Alphabet::new("01").unwrap());
+        assert_eq!(a.encode(&[5]), "101");
+
+        assert_eq!(
+            Alphabet::new("abcb").unwrap_err(),
+            "Duplicate symbol 'b' in alphabet"
+        );
+        assert!(Alphabet::new("a").is_err());
+        assert!(Alphabet::new("").is_err());
+    }
+}
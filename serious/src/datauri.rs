@@ -0,0 +1,278 @@
+use crate::{escape, simd};
+use base64_url::base64;
+use std::fmt::Display;
+
+/// Magic bytes at the start of a file and the MIME type they identify
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"\x00\x00\x01\x00", "image/x-icon"),
+    (b"%PDF-", "application/pdf"),
+    (b"wOFF", "font/woff"),
+    (b"wOF2", "font/woff2"),
+    (b"\x00\x01\x00\x00", "font/ttf"),
+    (b"OTTO", "font/otf"),
+    (b"\x00asm", "application/wasm"),
+    (b"ID3", "audio/mpeg"),
+    (b"OggS", "audio/ogg"),
+    (b"fLaC", "audio/flac"),
+    (b"\x1a\x45\xdf\xa3", "video/webm"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+];
+
+/// The longest media type [`MediaType::sniff`] returns, `text/plain;charset=utf-8`
+/// and `application/octet-stream`
+const LONGEST_SNIFFED: usize = 24;
+
+/// Sizes of the BITMAPCOREHEADER to BITMAPV5HEADER DIB headers that follow the BMP file header
+const BMP_DIB_SIZES: &[u32] = &[12, 40, 52, 56, 64, 108, 124];
+
+/// `BM` is also the start of plenty of text so the file size and DIB header
+/// size are checked too
+fn is_bmp(data: &[u8]) -> bool {
+    if data.len() < 18 || &data[..2] != b"BM" {
+        return false;
+    }
+    let u32_at = |i: usize| u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+    let dib = u32_at(14);
+    BMP_DIB_SIZES.contains(&dib) && u32_at(2) >= 14 + dib
+}
+
+/// The MIME type and parameters of a data URI like `image/png` or `text/plain;charset=utf-8`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MediaType {
+    pub mime: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl MediaType {
+    pub fn new<S: Into<String>>(mime: S) -> Self {
+        MediaType {
+            mime: mime.into(),
+            params: Vec::new(),
+        }
+    }
+
+    pub fn with_param<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+
+    /// Parse `type/subtype;key=value;...`
+    pub fn parse<I: AsRef<str>>(s: I) -> Result<Self, String> {
+        let s = s.as_ref();
+        let mut parts = s.split(';');
+        let mime = parts.next().unwrap_or_default().trim();
+        match mime.split_once('/') {
+            Some((t, st)) if is_token(t) && is_token(st) => (),
+            _ => return Err(format!("Invalid MIME type: {}", s)),
+        }
+        let mut media = MediaType::new(mime.to_ascii_lowercase());
+        for p in parts {
+            match p.split_once('=') {
+                Some((k, v)) if is_token(k.trim()) => media
+                    .params
+                    .push((k.trim().to_ascii_lowercase(), v.trim().to_string())),
+                _ => return Err(format!("Invalid MIME type parameter: {}", p)),
+            }
+        }
+        Ok(media)
+    }
+
+    /// Guess the media type from magic bytes, falling back to UTF-8 text or
+    /// `application/octet-stream`
+    pub fn sniff<T: AsRef<[u8]>>(data: T) -> Self {
+        let data = data.as_ref();
+        if let Some((_, mime)) = SIGNATURES.iter().find(|(m, _)| data.starts_with(m)) {
+            return MediaType::new(*mime);
+        }
+        if is_bmp(data) {
+            return MediaType::new("image/bmp");
+        }
+        if data.len() >= 12 && &data[..4] == b"RIFF" {
+            match &data[8..12] {
+                b"WEBP" => return MediaType::new("image/webp"),
+                b"WAVE" => return MediaType::new("audio/wav"),
+                _ => (),
+            }
+        }
+        if data.len() >= 12 && &data[4..8] == b"ftyp" {
+            return match &data[8..12] {
+                b"avif" => MediaType::new("image/avif"),
+                _ => MediaType::new("video/mp4"),
+            };
+        }
+        match std::str::from_utf8(data) {
+            Ok(s) => {
+                let start = s.trim_start().to_ascii_lowercase();
+                if start.starts_with("<svg")
+                    || (start.starts_with("<?xml") && start.contains("<svg"))
+                {
+                    MediaType::new("image/svg+xml")
+                } else if start.starts_with("<!doctype html") || start.starts_with("<html") {
+                    MediaType::new("text/html").with_param("charset", "utf-8")
+                } else {
+                    MediaType::new("text/plain").with_param("charset", "utf-8")
+                }
+            }
+            Err(_) => MediaType::new("application/octet-stream"),
+        }
+    }
+}
+
+impl Default for MediaType {
+    /// RFC 2397 default when a data URI omits the media type
+    fn default() -> Self {
+        MediaType::new("text/plain").with_param("charset", "US-ASCII")
+    }
+}
+
+impl Display for MediaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.mime)?;
+        for (k, v) in &self.params {
+            write!(f, ";{}={}", k, v)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$&-^_.+".contains(&b))
+}
+
+/// Encode as `data:<media type>;base64,<data>`
+pub(crate) fn data_uri_encode(data: &[u8], media: &MediaType) -> String {
+    format!("data:{};base64,{}", media, simd::base64_encode(data, false))
+}
+
+/// An upper bound on the length of [`data_uri_encode`] with a sniffed media type
+pub(crate) fn data_uri_encoded_len(n: usize) -> Option<usize> {
+    base64::encoded_len(n, true)?.checked_add("data:;base64,".len() + LONGEST_SNIFFED)
+}
+
+/// Decode a base64 or percent-encoded data URI
+pub(crate) fn data_uri_decode(s: &str) -> Result<(MediaType, Vec<u8>), String> {
+    let s = s.trim();
+    let rest = match s.get(..5) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("data:") => &s[5..],
+        _ => return Err("Data URI must start with 'data:'".to_string()),
+    };
+    let (meta, payload) = rest
+        .split_once(',')
+        .ok_or_else(|| "Data URI is missing ','".to_string())?;
+    let (meta, base64) = match meta.len().checked_sub(7).map(|i| meta.split_at(i)) {
+        Some((m, b)) if b.eq_ignore_ascii_case(";base64") => (m, true),
+        _ => (meta, false),
+    };
+    let media = match meta {
+        "" => MediaType::default(),
+        // A data URI may give only parameters like `;charset=utf-8`
+        m if m.starts_with(';') => {
+            let mut media = MediaType::parse(format!("text/plain{}", m))?;
+            if !media.params.iter().any(|(k, _)| k == "charset") {
+                media
+                    .params
+                    .insert(0, ("charset".to_string(), "US-ASCII".to_string()));
+            }
+            media
+        }
+        m => MediaType::parse(m)?,
+    };
+    let data = match base64 {
+        true => {
+            // Base64 payloads are often wrapped across lines in HTML and CSS
+            let payload = payload
+                .chars()
+                .filter(|c| !c.is_ascii_whitespace())
+                .collect::<String>();
+            let payload = escape::percent_decode(&payload, false)?;
+            simd::base64_decode(&payload, false)?
+        }
+        false => escape::percent_decode(payload, false)?,
+    };
+    Ok((media, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff() {
+        assert_eq!(
+            MediaType::sniff(b"\x89PNG\r\n\x1a\n\x00\x00").mime,
+            "image/png"
+        );
+        assert_eq!(MediaType::sniff(b"GIF89a...").mime, "image/gif");
+        assert_eq!(
+            MediaType::sniff(b"RIFF\x00\x00\x00\x00WEBPVP8 ").mime,
+            "image/webp"
+        );
+        let mut bmp =
+            b"BM\x46\x00\x00\x00\x00\x00\x00\x00\x36\x00\x00\x00\x28\x00\x00\x00".to_vec();
+        bmp.resize(0x46, 0);
+        assert_eq!(MediaType::sniff(&bmp).mime, "image/bmp");
+        assert_ne!(MediaType::sniff(&bmp[..17]).mime, "image/bmp");
+        assert_eq!(
+            MediaType::sniff("BMW 3 Series, BMW 5 Series").mime,
+            "text/plain"
+        );
+        assert_eq!(
+            MediaType::sniff("<?xml version=\"1.0\"?>\n<svg></svg>").mime,
+            "image/svg+xml"
+        );
+        assert_eq!(
+            MediaType::sniff("hello").to_string(),
+            "text/plain;charset=utf-8"
+        );
+        assert_eq!(
+            MediaType::sniff(b"\xfe\xed").mime,
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn longest_sniffed() {
+        for (_, mime) in SIGNATURES {
+            assert!(mime.len() <= LONGEST_SNIFFED, "{}", mime);
+        }
+        for data in [&b"text"[..], b"\xfe\xed", b"<html>"] {
+            assert!(MediaType::sniff(data).to_string().len() <= LONGEST_SNIFFED);
+        }
+    }
+
+    #[test]
+    fn data_uri() {
+        let media = MediaType::new("image/gif");
+        let res = data_uri_encode(b"GIF89a", &media);
+        assert_eq!(res, "data:image/gif;base64,R0lGODlh");
+        assert_eq!(data_uri_decode(&res).unwrap(), (media, b"GIF89a".to_vec()));
+
+        let (media, data) = data_uri_decode("data:,Hello%2C%20World!").unwrap();
+        assert_eq!(media.to_string(), "text/plain;charset=US-ASCII");
+        assert_eq!(data, b"Hello, World!");
+
+        let (media, data) =
+            data_uri_decode("DATA:text/html;charset=utf-8;base64,PGI+\naGk8L2I+").unwrap();
+        assert_eq!(media.mime, "text/html");
+        assert_eq!(
+            media.params,
+            vec![("charset".to_string(), "utf-8".to_string())]
+        );
+        assert_eq!(data, b"<b>hi</b>");
+
+        let (media, _) = data_uri_decode("data:;charset=utf-8,hi").unwrap();
+        assert_eq!(media.to_string(), "text/plain;charset=utf-8");
+
+        assert!(data_uri_decode("image/png;base64,AAAA").is_err());
+        assert!(data_uri_decode("data:image/png;base64").is_err());
+        assert!(data_uri_decode("data:image;base64,AAAA").is_err());
+        assert!(data_uri_decode("data:,a b").is_err());
+    }
+}
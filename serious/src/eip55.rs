@@ -0,0 +1,100 @@
+use crate::simd;
+use sha3::{Digest, Keccak256};
+
+/// Mixed-case hex where each letter is uppercase when the matching nibble of
+/// the Keccak-256 hash of the lowercase hex is 8 or more. EIP-1191 puts the
+/// chain id and `0x` in front of the hex before hashing.
+fn checksum(lower: &str, chain_id: Option<u64>) -> String {
+    let mut hasher = Keccak256::new();
+    if let Some(id) = chain_id {
+        hasher.update(format!("{}0x", id));
+    }
+    hasher.update(lower);
+    let hash = hasher.finalize();
+    lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| match hash[i / 2] >> (4 * (1 - i % 2)) & 0xf {
+            8.. => c.to_ascii_uppercase(),
+            _ => c,
+        })
+        .collect()
+}
+
+/// Each hex digit takes a nibble of the hash so at most 32 bytes can be checksummed
+fn check_len(len: usize) -> Result<(), String> {
+    match len {
+        0..=32 => Ok(()),
+        _ => Err(format!(
+            "EIP-55 checksums cover at most 32 bytes not {}",
+            len
+        )),
+    }
+}
+
+pub(crate) fn eip55_encode(data: &[u8], chain_id: Option<u64>) -> Result<String, String> {
+    check_len(data.len())?;
+    Ok(format!(
+        "0x{}",
+        checksum(&simd::hex_encode(data, false), chain_id)
+    ))
+}
+
+/// All lowercase and all uppercase input has no checksum so it's accepted as is
+pub(crate) fn eip55_decode(s: &str, chain_id: Option<u64>) -> Result<Vec<u8>, String> {
+    let s = s.trim();
+    let hex = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    let data = simd::hex_decode(hex.as_bytes())?;
+    check_len(data.len())?;
+    let mixed =
+        hex.bytes().any(|b| b.is_ascii_lowercase()) && hex.bytes().any(|b| b.is_ascii_uppercase());
+    if mixed && checksum(&hex.to_ascii_lowercase(), chain_id) != hex {
+        return Err(match chain_id {
+            Some(id) => format!("Invalid EIP-1191 checksum for chain {}", id),
+            None => "Invalid EIP-55 checksum".to_string(),
+        });
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eip55() {
+        for address in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let data = eip55_decode(address, None).unwrap();
+            assert_eq!(eip55_encode(&data, None).unwrap(), address);
+            assert_eq!(
+                eip55_decode(&address.to_ascii_lowercase(), None).unwrap(),
+                data
+            );
+            assert_eq!(
+                eip55_decode(&address[2..].to_ascii_uppercase(), None).unwrap(),
+                data
+            );
+        }
+        assert!(eip55_decode("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD", None).is_err());
+        assert!(eip55_decode("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAe", None).is_err());
+        assert!(eip55_encode(&[0u8; 33], None).is_err());
+        assert!(eip55_decode(&"00".repeat(33), None).is_err());
+    }
+
+    #[test]
+    fn eip1191() {
+        let rsk = "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD";
+        let data = eip55_decode(rsk, Some(30)).unwrap();
+        assert_eq!(eip55_encode(&data, Some(30)).unwrap(), rsk);
+        assert!(eip55_decode(rsk, None).is_err());
+        assert!(eip55_decode(rsk, Some(31)).is_err());
+    }
+}
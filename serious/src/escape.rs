@@ -0,0 +1,367 @@
+use std::fmt::Write;
+
+const QP_LINE_LENGTH: usize = 76;
+
+fn hex_value(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|d| d as u8)
+}
+
+fn is_unreserved(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"-._~".contains(&b)
+}
+
+fn is_reserved(b: u8) -> bool {
+    b":/?#[]@!$&'()*+,;=".contains(&b)
+}
+
+/// RFC 3986 percent encoding. With `form` set, encodes like
+/// `application/x-www-form-urlencoded` where a space becomes `+`.
+pub(crate) fn percent_encode(data: &[u8], form: bool) -> String {
+    let mut out = String::with_capacity(data.len() * 3);
+    for &b in data {
+        match b {
+            b' ' if form => out.push('+'),
+            b'~' if form => out.push_str("%7E"),
+            b'*' if form => out.push('*'),
+            b if is_unreserved(b) => out.push(b as char),
+            b => write!(out, "%{:02X}", b).unwrap(),
+        }
+    }
+    out
+}
+
+pub(crate) fn percent_decode(s: &str, form: bool) -> Result<Vec<u8>, String> {
+    let s = s.as_bytes();
+    let mut out = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        match s[i] {
+            b'%' => match (
+                s.get(i + 1).and_then(|c| hex_value(*c)),
+                s.get(i + 2).and_then(|c| hex_value(*c)),
+            ) {
+                (Some(hi), Some(lo)) => {
+                    out.push(hi << 4 | lo);
+                    i += 2;
+                }
+                _ => return Err(format!("Invalid percent escape at position {}", i)),
+            },
+            b'+' if form => out.push(b' '),
+            b if is_unreserved(b) || is_reserved(b) => out.push(b),
+            b => {
+                return Err(format!(
+                    "Invalid character '{}' at position {}",
+                    (b as char).escape_default(),
+                    i
+                ))
+            }
+        }
+        i += 1;
+    }
+    Ok(out)
+}
+
+/// RFC 2045 quoted-printable. CRLF pairs are kept as hard line breaks,
+/// lines are wrapped with soft line breaks at 76 characters.
+pub(crate) fn qp_encode(data: &[u8]) -> String {
+    let is_break = |i: usize| data.get(i) == Some(&b'\r') && data.get(i + 1) == Some(&b'\n');
+    let mut out = String::with_capacity(data.len() * 3);
+    let mut line_length = 0;
+    let mut i = 0;
+    while i < data.len() {
+        if is_break(i) {
+            out.push_str("\r\n");
+            line_length = 0;
+            i += 2;
+            continue;
+        }
+        let token = match data[i] {
+            b' ' | b'\t' if i + 1 < data.len() && !is_break(i + 1) => (data[i] as char).to_string(),
+            b @ 33..=60 | b @ 62..=126 => (b as char).to_string(),
+            b => format!("={:02X}", b),
+        };
+        // Leave room for the '=' of a soft line break
+        if line_length + token.len() > QP_LINE_LENGTH - 1 {
+            out.push_str("=\r\n");
+            line_length = 0;
+        }
+        line_length += token.len();
+        out.push_str(&token);
+        i += 1;
+    }
+    out
+}
+
+pub(crate) fn qp_decode(s: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut lines = s.split('\n').enumerate().peekable();
+    while let Some((n, line)) = lines.next() {
+        let (line, crlf) = match line.strip_suffix('\r') {
+            Some(l) => (l, true),
+            None => (line, false),
+        };
+        // Trailing whitespace is transport padding
+        let line = line.trim_end_matches([' ', '\t']).as_bytes();
+        let (line, soft) = match line.strip_suffix(b"=") {
+            Some(l) => (l, true),
+            None => (line, false),
+        };
+        let mut i = 0;
+        while i < line.len() {
+            match line[i] {
+                b'=' => match (line.get(i + 1), line.get(i + 2)) {
+                    (
+                        Some(hi @ (b'0'..=b'9' | b'A'..=b'F')),
+                        Some(lo @ (b'0'..=b'9' | b'A'..=b'F')),
+                    ) => {
+                        out.push(hex_value(*hi).unwrap() << 4 | hex_value(*lo).unwrap());
+                        i += 2;
+                    }
+                    _ => return Err(format!("Invalid quoted-printable escape on line {}", n + 1)),
+                },
+                b @ (b' ' | b'\t' | 33..=126) => out.push(b),
+                b => {
+                    return Err(format!(
+                        "Invalid quoted-printable character '{}' on line {}",
+                        (b as char).escape_default(),
+                        n + 1
+                    ))
+                }
+            }
+            i += 1;
+        }
+        if !soft && lines.peek().is_some() {
+            if crlf {
+                out.push(b'\r');
+            }
+            out.push(b'\n');
+        }
+    }
+    Ok(out)
+}
+
+/// The contents of a JSON string without the surrounding quotes.
+/// JSON strings can only hold text so the input must be UTF-8.
+pub(crate) fn json_encode(data: &[u8]) -> Result<String, String> {
+    let text = std::str::from_utf8(data)
+        .map_err(|e| format!("Input to json is not valid UTF-8 - {}", e))?;
+    let mut out = String::with_capacity(data.len() + 2);
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+pub(crate) fn json_decode(s: &str) -> Result<Vec<u8>, String> {
+    fn hex4(chars: &mut std::str::Chars) -> Result<u32, String> {
+        let digits = chars.take(4).collect::<String>();
+        if digits.chars().count() != 4 {
+            return Err("Truncated JSON \\u escape".to_string());
+        }
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid JSON escape \\u{}", digits));
+        }
+        Ok(u32::from_str_radix(&digits, 16).unwrap())
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('"') => out.push('"'),
+                Some('\\') => out.push('\\'),
+                Some('/') => out.push('/'),
+                Some('b') => out.push('\u{08}'),
+                Some('f') => out.push('\u{0c}'),
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('t') => out.push('\t'),
+                Some('u') => {
+                    let mut code = hex4(&mut chars)?;
+                    if (0xD800..0xDC00).contains(&code) {
+                        if chars.next() != Some('\\') || chars.next() != Some('u') {
+                            return Err("Unpaired surrogate in JSON string".to_string());
+                        }
+                        let low = hex4(&mut chars)?;
+                        if !(0xDC00..0xE000).contains(&low) {
+                            return Err("Unpaired surrogate in JSON string".to_string());
+                        }
+                        code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                    }
+                    match char::from_u32(code) {
+                        Some(c) => out.push(c),
+                        None => return Err("Unpaired surrogate in JSON string".to_string()),
+                    }
+                }
+                Some(e) => return Err(format!("Invalid JSON escape \\{}", e)),
+                None => return Err("Truncated JSON escape".to_string()),
+            },
+            '"' => return Err("Unescaped quote in JSON string".to_string()),
+            c if c < ' ' => {
+                return Err(format!(
+                    "Unescaped control character '{}' in JSON string",
+                    c.escape_default()
+                ))
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out.into_bytes())
+}
+
+/// C and Rust string literal escapes using `\xNN` for anything
+/// that isn't printable ASCII.
+pub(crate) fn c_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len() * 4);
+    for &b in data {
+        match b {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\'' => out.push_str("\\'"),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            b'\0' => out.push_str("\\0"),
+            0x20..=0x7e => out.push(b as char),
+            b => write!(out, "\\x{:02x}", b).unwrap(),
+        }
+    }
+    out
+}
+
+pub(crate) fn c_decode(s: &str) -> Result<Vec<u8>, String> {
+    let s = s.as_bytes();
+    let mut out = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        match s[i] {
+            b'\\' => {
+                i += 1;
+                let b = match s.get(i) {
+                    Some(b'\\') => b'\\',
+                    Some(b'"') => b'"',
+                    Some(b'\'') => b'\'',
+                    Some(b'n') => b'\n',
+                    Some(b'r') => b'\r',
+                    Some(b't') => b'\t',
+                    Some(b'0') => b'\0',
+                    Some(b'a') => 0x07,
+                    Some(b'b') => 0x08,
+                    Some(b'f') => 0x0c,
+                    Some(b'v') => 0x0b,
+                    Some(b'x') => match (
+                        s.get(i + 1).and_then(|c| hex_value(*c)),
+                        s.get(i + 2).and_then(|c| hex_value(*c)),
+                    ) {
+                        (Some(hi), Some(lo)) => {
+                            i += 2;
+                            hi << 4 | lo
+                        }
+                        _ => return Err(format!("Invalid \\x escape at position {}", i - 1)),
+                    },
+                    Some(e) => {
+                        return Err(format!(
+                            "Invalid escape '\\{}' at position {}",
+                            (*e as char).escape_default(),
+                            i - 1
+                        ))
+                    }
+                    None => return Err("Truncated escape at end of input".to_string()),
+                };
+                out.push(b);
+            }
+            b'"' => return Err(format!("Unescaped quote at position {}", i)),
+            b @ 0x20..=0x7e => out.push(b),
+            b => {
+                return Err(format!(
+                    "Unescaped character '{}' at position {}",
+                    (b as char).escape_default(),
+                    i
+                ))
+            }
+        }
+        i += 1;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent() {
+        let data = b"a b+c~d*e/\xff";
+        assert_eq!(percent_encode(data, false), "a%20b%2Bc~d%2Ae%2F%FF");
+        assert_eq!(percent_encode(data, true), "a+b%2Bc%7Ed*e%2F%FF");
+        assert_eq!(percent_decode("a%20b%2Bc~d%2ae/%FF", false).unwrap(), data);
+        assert_eq!(percent_decode("a+b%2Bc%7Ed*e%2F%FF", true).unwrap(), data);
+        assert_eq!(percent_decode("a+b", false).unwrap(), b"a+b");
+        assert!(percent_decode("a b", false).is_err());
+        assert!(percent_decode("%2", false).is_err());
+        assert!(percent_decode("%zz", true).is_err());
+    }
+
+    #[test]
+    fn quoted_printable() {
+        let data = "Caf\u{e9} = caf\u{e9} \r\nend\t".as_bytes();
+        let res = qp_encode(data);
+        assert_eq!(res, "Caf=C3=A9 =3D caf=C3=A9=20\r\nend=09");
+        assert_eq!(qp_decode(&res).unwrap(), data);
+        assert_eq!(
+            qp_decode("soft=\r\nbreak  \r\nnext").unwrap(),
+            b"softbreak\r\nnext"
+        );
+
+        let long = vec![b'x'; 200];
+        let res = qp_encode(&long);
+        assert!(res.split("\r\n").all(|l| l.len() <= QP_LINE_LENGTH));
+        assert_eq!(qp_decode(&res).unwrap(), long);
+
+        assert!(qp_decode("bad=4").is_err());
+        assert!(qp_decode("bad=e9").is_err());
+        assert!(qp_decode("caf\u{e9}").is_err());
+    }
+
+    #[test]
+    fn json() {
+        let data = "say \"hi\"\\\n\u{1}\u{1F600}".as_bytes();
+        let res = json_encode(data).unwrap();
+        assert_eq!(res, "say \\\"hi\\\"\\\\\\n\\u0001\u{1F600}");
+        assert_eq!(json_decode(&res).unwrap(), data);
+        assert_eq!(
+            json_decode("\\ud83d\\ude00\\/").unwrap(),
+            "\u{1F600}/".as_bytes()
+        );
+        assert!(json_decode("\\ud83d").is_err());
+        assert!(json_decode("\\ude00").is_err());
+        assert!(json_decode("a\"b").is_err());
+        assert!(json_decode("a\nb").is_err());
+        assert!(json_decode("\\q").is_err());
+        assert!(json_encode(b"caf\xe9").is_err());
+    }
+
+    #[test]
+    fn c_escape() {
+        let data = b"it's \"x\"\\\n\0\x7f\xff";
+        let res = c_encode(data);
+        assert_eq!(res, "it\\'s \\\"x\\\"\\\\\\n\\0\\x7f\\xff");
+        assert_eq!(c_decode(&res).unwrap(), data);
+        assert_eq!(c_decode("\\a\\v\\x4A").unwrap(), b"\x07\x0bJ");
+        assert!(c_decode("\\x4").is_err());
+        assert!(c_decode("\\q").is_err());
+        assert!(c_decode("a\"b").is_err());
+        assert!(c_decode("\u{e9}").is_err());
+    }
+}
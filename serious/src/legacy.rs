@@ -0,0 +1,502 @@
+use std::fmt::Write;
+
+const UU_LINE_BYTES: usize = 45;
+const YENC_LINE_LENGTH: usize = 128;
+const BINHEX_INTRO: &str = "(This file must be converted with BinHex 4.0)";
+const BINHEX_ALPHABET: &[u8; 64] =
+    b"!\"#$%&'()*+,-012345689@ABCDEFGHIJKLMNPQRSTUVXYZ[`abcdefhijklmpqr";
+const BINHEX_LINE_LENGTH: usize = 64;
+const BINHEX_RUN: u8 = 0x90;
+
+/// The file name and mode carried by uuencode, yEnc and BinHex
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FileHeader {
+    pub name: String,
+    /// Unix permission bits, only stored by uuencode
+    pub mode: u32,
+}
+
+impl FileHeader {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        FileHeader {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+impl Default for FileHeader {
+    fn default() -> Self {
+        FileHeader {
+            name: "data".to_string(),
+            mode: 0o644,
+        }
+    }
+}
+
+fn lines(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    data.split(|b| *b == b'\n')
+        .map(|l| l.strip_suffix(b"\r").unwrap_or(l))
+}
+
+fn uu_char(bits: u8) -> char {
+    match bits & 0x3f {
+        0 => '`',
+        b => (b + 32) as char,
+    }
+}
+
+pub(crate) fn uu_encode(data: &[u8], header: &FileHeader) -> String {
+    let mut out = format!("begin {:o} {}\n", header.mode, header.name);
+    for chunk in data.chunks(UU_LINE_BYTES) {
+        out.push(uu_char(chunk.len() as u8));
+        for group in chunk.chunks(3) {
+            let mut g = [0u8; 3];
+            g[..group.len()].copy_from_slice(group);
+            out.push(uu_char(g[0] >> 2));
+            out.push(uu_char(g[0] << 4 | g[1] >> 4));
+            out.push(uu_char(g[1] << 2 | g[2] >> 6));
+            out.push(uu_char(g[2]));
+        }
+        out.push('\n');
+    }
+    out.push_str("`\nend");
+    out
+}
+
+/// The exact length of [`uu_encode`]
+pub(crate) fn uu_encoded_len(n: usize, header: &FileHeader) -> Option<usize> {
+    let begin = format!("begin {:o} {}\n", header.mode, header.name).len();
+    // Every line has a length character, 4 characters for each 3 bytes and a line break
+    let full = (n / UU_LINE_BYTES).checked_mul(UU_LINE_BYTES / 3 * 4 + 2)?;
+    let rest = match n % UU_LINE_BYTES {
+        0 => 0,
+        r => r.div_ceil(3) * 4 + 2,
+    };
+    full.checked_add(begin + rest + "`\nend".len())
+}
+
+pub(crate) fn uu_decode(data: &[u8]) -> Result<(FileHeader, Vec<u8>), String> {
+    let mut lines = lines(data).skip_while(|l| !l.starts_with(b"begin "));
+    let begin = String::from_utf8_lossy(lines.next().ok_or("Missing uuencode begin line")?);
+    let mut fields = begin.splitn(3, ' ').skip(1);
+    let mode = fields
+        .next()
+        .and_then(|m| u32::from_str_radix(m, 8).ok())
+        .ok_or("Invalid uuencode file mode")?;
+    let name = fields.next().ok_or("Missing uuencode file name")?;
+    let header = FileHeader::new(name).with_mode(mode);
+
+    let mut out = Vec::new();
+    for line in lines.by_ref() {
+        let length = match line.first() {
+            Some(c @ 32..=96) => usize::from((c - 32) & 0x3f),
+            _ => return Err("Invalid uuencode line".to_string()),
+        };
+        if length == 0 {
+            break;
+        }
+        let chars = &line[1..];
+        let width = length.div_ceil(3) * 4;
+        if chars.len() < width || chars.iter().any(|c| !(32..=96).contains(c)) {
+            return Err("Invalid uuencode line".to_string());
+        }
+        let mut decoded = Vec::with_capacity(length + 2);
+        for g in chars[..width].chunks(4) {
+            let g = [g[0] - 32, g[1] - 32, g[2] - 32, g[3] - 32].map(|c| c & 0x3f);
+            decoded.push(g[0] << 2 | g[1] >> 4);
+            decoded.push(g[1] << 4 | g[2] >> 2);
+            decoded.push(g[2] << 6 | g[3]);
+        }
+        out.extend_from_slice(&decoded[..length]);
+    }
+    match lines.next() {
+        Some(b"end") => Ok((header, out)),
+        _ => Err("Missing uuencode end line".to_string()),
+    }
+}
+
+pub(crate) fn yenc_encode(data: &[u8], header: &FileHeader) -> Vec<u8> {
+    let mut out = format!(
+        "=ybegin line={} size={} name={}\n",
+        YENC_LINE_LENGTH,
+        data.len(),
+        header.name
+    )
+    .into_bytes();
+    let mut line_length = 0;
+    for (i, b) in data.iter().enumerate() {
+        let c = b.wrapping_add(42);
+        let last = line_length + 1 >= YENC_LINE_LENGTH || i + 1 == data.len();
+        let escape = match c {
+            0x00 | b'\n' | b'\r' | b'=' => true,
+            b'\t' | b' ' => line_length == 0 || last,
+            b'.' => line_length == 0,
+            _ => false,
+        };
+        if escape {
+            out.push(b'=');
+            out.push(c.wrapping_add(64));
+            line_length += 2;
+        } else {
+            out.push(c);
+            line_length += 1;
+        }
+        if line_length >= YENC_LINE_LENGTH {
+            out.push(b'\n');
+            line_length = 0;
+        }
+    }
+    if line_length > 0 {
+        out.push(b'\n');
+    }
+    out.extend_from_slice(
+        format!(
+            "=yend size={} crc32={:08x}",
+            data.len(),
+            crc32fast::hash(data)
+        )
+        .as_bytes(),
+    );
+    out
+}
+
+/// An upper bound on the length of [`yenc_encode`]. Escapes double a byte and
+/// every line but the last holds at least `YENC_LINE_LENGTH` bytes.
+pub(crate) fn yenc_encoded_len(n: usize, header: &FileHeader) -> Option<usize> {
+    let begin = format!(
+        "=ybegin line={} size={} name={}\n",
+        YENC_LINE_LENGTH, n, header.name
+    )
+    .len();
+    let end = format!("=yend size={} crc32={:08x}", n, 0).len();
+    let body = n.checked_mul(2)?;
+    body.checked_add(body / YENC_LINE_LENGTH + 1)?
+        .checked_add(begin + end)
+}
+
+/// Find `key=value` in a yEnc header line. The name is always last and may contain spaces.
+fn yenc_field<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    if key == "name" {
+        return line.split_once(" name=").map(|(_, n)| n);
+    }
+    line.split(' ')
+        .find_map(|f| f.strip_prefix(key).and_then(|f| f.strip_prefix('=')))
+}
+
+pub(crate) fn yenc_decode(data: &[u8]) -> Result<(FileHeader, Vec<u8>), String> {
+    let mut lines = lines(data).skip_while(|l| !l.starts_with(b"=ybegin "));
+    let begin = String::from_utf8_lossy(lines.next().ok_or("Missing yEnc =ybegin line")?);
+    if yenc_field(&begin, "part").is_some() {
+        return Err("Multipart yEnc is not supported".to_string());
+    }
+    let size = yenc_field(&begin, "size")
+        .and_then(|s| s.parse::<usize>().ok())
+        .ok_or("Invalid yEnc size")?;
+    let name = yenc_field(&begin, "name").ok_or("Missing yEnc file name")?;
+    let header = FileHeader::new(name);
+
+    let mut out = Vec::with_capacity(size);
+    let mut end = None;
+    for line in lines {
+        if line.starts_with(b"=yend") {
+            end = Some(String::from_utf8_lossy(line));
+            break;
+        }
+        let mut bytes = line.iter();
+        while let Some(b) = bytes.next() {
+            match b {
+                b'=' => match bytes.next() {
+                    Some(e) => out.push(e.wrapping_sub(64).wrapping_sub(42)),
+                    None => return Err("Invalid yEnc escape at end of line".to_string()),
+                },
+                b => out.push(b.wrapping_sub(42)),
+            }
+        }
+    }
+    let end = end.ok_or("Missing yEnc =yend line")?;
+    let end_size = yenc_field(&end, "size").and_then(|s| s.parse::<usize>().ok());
+    if out.len() != size || end_size != Some(size) {
+        return Err(format!(
+            "yEnc size mismatch, expected {} bytes but found {}",
+            size,
+            out.len()
+        ));
+    }
+    if let Some(crc) = yenc_field(&end, "crc32") {
+        let crc = u32::from_str_radix(crc, 16).map_err(|_| "Invalid yEnc crc32")?;
+        if crc != crc32fast::hash(&out) {
+            return Err("yEnc crc32 mismatch".to_string());
+        }
+    }
+    Ok((header, out))
+}
+
+/// CRC-16/XMODEM as used by BinHex
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for b in data {
+        crc ^= u16::from(*b) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 == 0 {
+                crc << 1
+            } else {
+                crc << 1 ^ 0x1021
+            };
+        }
+    }
+    crc
+}
+
+fn rle90_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let b = data[i];
+        let run = data[i..].iter().take(255).take_while(|c| **c == b).count();
+        out.push(b);
+        if b == BINHEX_RUN {
+            out.push(0);
+        }
+        if run > 2 {
+            out.push(BINHEX_RUN);
+            out.push(run as u8);
+            i += run;
+        } else {
+            i += 1;
+        }
+    }
+    out
+}
+
+fn rle90_decode(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(b) = bytes.next() {
+        match *b {
+            BINHEX_RUN => match bytes.next() {
+                Some(0) => out.push(BINHEX_RUN),
+                Some(n) => {
+                    let last = *out.last().ok_or("Invalid BinHex run length")?;
+                    out.extend(std::iter::repeat_n(last, usize::from(*n) - 1));
+                }
+                None => return Err("Truncated BinHex run length".to_string()),
+            },
+            b => out.push(b),
+        }
+    }
+    Ok(out)
+}
+
+pub(crate) fn binhex_encode(data: &[u8], header: &FileHeader) -> Result<String, String> {
+    let name = header.name.as_bytes();
+    if name.is_empty() || name.len() > 63 {
+        return Err("BinHex file names must be 1 to 63 bytes".to_string());
+    }
+    let fork = u32::try_from(data.len())
+        .map_err(|_| "BinHex data forks must be less than 4GiB".to_string())?;
+    let mut info = vec![name.len() as u8];
+    info.extend_from_slice(name);
+    info.push(0);
+    // Unknown file type and creator with no Finder flags
+    info.extend_from_slice(b"????????\0\0");
+    info.extend_from_slice(&fork.to_be_bytes());
+    info.extend_from_slice(&0u32.to_be_bytes());
+
+    let mut stream = info.clone();
+    stream.extend_from_slice(&crc16(&info).to_be_bytes());
+    stream.extend_from_slice(data);
+    stream.extend_from_slice(&crc16(data).to_be_bytes());
+    stream.extend_from_slice(&crc16(&[]).to_be_bytes());
+
+    let mut chars = String::from(":");
+    for group in rle90_encode(&stream).chunks(3) {
+        let mut g = [0u8; 3];
+        g[..group.len()].copy_from_slice(group);
+        let sextets = [
+            g[0] >> 2,
+            g[0] << 4 | g[1] >> 4,
+            g[1] << 2 | g[2] >> 6,
+            g[2],
+        ];
+        for s in &sextets[..group.len() + 1] {
+            chars.push(BINHEX_ALPHABET[usize::from(s & 0x3f)] as char);
+        }
+    }
+    chars.push(':');
+
+    let mut out = format!("{}\n", BINHEX_INTRO);
+    for (i, line) in chars.as_bytes().chunks(BINHEX_LINE_LENGTH).enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        write!(out, "{}", String::from_utf8_lossy(line)).unwrap();
+    }
+    Ok(out)
+}
+
+/// An upper bound on the length of [`binhex_encode`]. Run length encoding at
+/// most doubles the stream since only `0x90` bytes grow.
+pub(crate) fn binhex_encoded_len(n: usize, header: &FileHeader) -> Option<usize> {
+    // The name and its length, version, type, creator, flags, fork lengths and CRCs
+    let stream = n.checked_add(header.name.len() + 26)?;
+    let rle = stream.checked_mul(2)?;
+    // 4 characters for every 3 bytes between the colons
+    let chars = rle.checked_add(rle.div_ceil(3))?.checked_add(2)?;
+    chars
+        .checked_add(chars / BINHEX_LINE_LENGTH)?
+        .checked_add(BINHEX_INTRO.len() + 1)
+}
+
+pub(crate) fn binhex_decode(data: &[u8]) -> Result<(FileHeader, Vec<u8>), String> {
+    let start = data
+        .windows(BINHEX_INTRO.len())
+        .position(|w| w == BINHEX_INTRO.as_bytes())
+        .ok_or("Missing BinHex 4.0 header line")?;
+    let data = &data[start + BINHEX_INTRO.len()..];
+    let open = data
+        .iter()
+        .position(|b| *b == b':')
+        .ok_or("Missing BinHex start")?;
+
+    let mut packed = Vec::with_capacity(data.len());
+    let mut bits = 0u32;
+    let mut nbits = 0;
+    let mut closed = false;
+    for b in &data[open + 1..] {
+        match b {
+            b':' => {
+                closed = true;
+                break;
+            }
+            b'\r' | b'\n' | b' ' | b'\t' => continue,
+            b => {
+                let sextet = BINHEX_ALPHABET
+                    .iter()
+                    .position(|c| c == b)
+                    .ok_or_else(|| format!("Invalid BinHex character '{}'", *b as char))?;
+                bits = bits << 6 | sextet as u32;
+                nbits += 6;
+                if nbits >= 8 {
+                    nbits -= 8;
+                    packed.push((bits >> nbits) as u8);
+                }
+            }
+        }
+    }
+    if !closed {
+        return Err("Missing BinHex end".to_string());
+    }
+
+    let stream = rle90_decode(&packed)?;
+    let truncated = || "Truncated BinHex data".to_string();
+    let name_len = usize::from(*stream.first().ok_or_else(truncated)?);
+    let info_len = 1 + name_len + 1 + 4 + 4 + 2 + 4 + 4;
+    let info = stream.get(..info_len).ok_or_else(truncated)?;
+    let read_u32 =
+        |at: usize| u32::from_be_bytes([info[at], info[at + 1], info[at + 2], info[at + 3]]);
+    let data_len = read_u32(info_len - 8) as usize;
+    let rsrc_len = read_u32(info_len - 4) as usize;
+
+    let mut offset = 0;
+    let mut section = |len: usize, what: &str| -> Result<&[u8], String> {
+        let bytes = stream.get(offset..offset + len).ok_or_else(truncated)?;
+        let crc = stream
+            .get(offset + len..offset + len + 2)
+            .ok_or_else(truncated)?;
+        offset += len + 2;
+        if crc16(bytes).to_be_bytes() != crc {
+            return Err(format!("BinHex {} crc mismatch", what));
+        }
+        Ok(bytes)
+    };
+    let info = section(info_len, "header")?;
+    let name = String::from_utf8_lossy(&info[1..1 + name_len]).to_string();
+    let out = section(data_len, "data fork")?.to_vec();
+    section(rsrc_len, "resource fork")?;
+    Ok((FileHeader::new(name), out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn uuencode() {
+        let header = FileHeader::new("hello world.txt").with_mode(0o600);
+        let res = uu_encode(b"Cat", &header);
+        assert_eq!(res, "begin 600 hello world.txt\n#0V%T\n`\nend");
+        assert_eq!(
+            uu_decode(res.as_bytes()).unwrap(),
+            (header, b"Cat".to_vec())
+        );
+
+        // Old encoders use spaces instead of backticks
+        let (_, out) = uu_decode(b"begin 644 x\r\n#0V%T\r\n \r\nend\r\n").unwrap();
+        assert_eq!(out, b"Cat");
+        assert!(uu_decode(b"begin 644 x\n#0V%T\n`\n").is_err());
+        assert!(uu_decode(b"#0V%T\n`\nend").is_err());
+        assert!(uu_decode(b"begin 644 x\n#0V\n`\nend").is_err());
+    }
+
+    #[test]
+    fn yenc() {
+        let mut rng = rand::thread_rng();
+        let mut buffer = vec![0u8; 1000];
+        rng.fill_bytes(buffer.as_mut_slice());
+        // Bytes that encode to critical characters
+        buffer.extend_from_slice(&[214, 224, 227, 19, 4, 246]);
+
+        let header = FileHeader::new("my file.bin");
+        let res = yenc_encode(&buffer, &header);
+        assert!(res.starts_with(b"=ybegin line=128 size=1006 name=my file.bin\n"));
+        assert!(res
+            .split(|b| *b == b'\n')
+            .all(|l| l.len() <= YENC_LINE_LENGTH + 1 && !l.contains(&b'\r') && !l.contains(&0)));
+        let (h, out) = yenc_decode(&res).unwrap();
+        assert_eq!(h.name, header.name);
+        assert_eq!(out, buffer);
+
+        // Flip a plain data byte so only the crc catches it
+        let mut bad = res.clone();
+        let at = (bad.len() / 2..)
+            .find(|i| !b"\n=".contains(&bad[*i]) && !b"\n=".contains(&bad[*i - 1]))
+            .unwrap();
+        bad[at] = if bad[at] == b'a' { b'b' } else { b'a' };
+        assert_eq!(yenc_decode(&bad).unwrap_err(), "yEnc crc32 mismatch");
+        assert!(yenc_decode(&res[..res.len() - 30]).is_err());
+    }
+
+    #[test]
+    fn binhex() {
+        assert_eq!(crc16(b"123456789"), 0x31c3);
+        let runs = [1u8, 1, 1, 1, 0x90, 0x90, 0x90, 0x90, 2, 0x90, 3];
+        assert_eq!(rle90_decode(&rle90_encode(&runs)).unwrap(), runs);
+
+        let mut rng = rand::thread_rng();
+        let mut buffer = vec![0u8; 1000];
+        rng.fill_bytes(buffer.as_mut_slice());
+        buffer.extend_from_slice(&[0u8; 300]);
+
+        let header = FileHeader::new("Read Me");
+        let res = binhex_encode(&buffer, &header).unwrap();
+        assert!(res.starts_with(BINHEX_INTRO));
+        assert!(res.lines().all(|l| l.len() <= BINHEX_LINE_LENGTH));
+        let (h, out) = binhex_decode(res.as_bytes()).unwrap();
+        assert_eq!(h.name, header.name);
+        assert_eq!(out, buffer);
+
+        let mut bad = res.clone().into_bytes();
+        let at = (bad.len() / 2..).find(|i| bad[*i] != b'\n').unwrap();
+        bad[at] = if bad[at] == b'!' { b'"' } else { b'!' };
+        assert!(binhex_decode(&bad).is_err());
+        assert!(binhex_decode(&res.as_bytes()[..res.len() - 1]).is_err());
+        assert!(binhex_encode(b"", &FileHeader::new("")).is_err());
+    }
+}
@@ -9,46 +9,40 @@
 
 use clap::{App, Arg};
 use serious::Encoding::{self as Code, *};
-use std::fs::File;
+use serious::{Alphabet, Charset, Compression, FileHeader, MediaType, Pipeline};
+use std::fs::{File, OpenOptions};
 use std::io::{self, Read, Write};
 use std::path::PathBuf;
 use stringreader::StringReader;
 
 fn main() {
-    let inencodings = vec![
-        Blob.to_string(),
-        Binary.to_string(),
-        Base10.to_string(),
-        Base58.to_string(),
-        Base62.to_string(),
-        Base64.to_string(),
-        Base64Url.to_string(),
-        BitCoin.to_string(),
-        Flickr.to_string(),
-        LowHex.to_string(),
-        Monero.to_string(),
-        Ripple.to_string(),
-    ];
-    let inencs = inencodings
+    let names = Code::values()
+        .iter()
+        .map(|e| e.name())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    let input_help = format!(
+        "The input encoding to parse. [possible values: {}, custom]",
+        names
+    );
+    let output_help = format!("The output encoding. [possible values: {}, custom]", names);
+
+    let compressions = Compression::values()
         .iter()
-        .map(|e| e.as_str())
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>();
+    let compressions = compressions
+        .iter()
+        .map(|c| c.as_str())
         .collect::<Vec<&str>>();
 
-    let outencs = vec![
-        "blob",
-        "binary",
-        "base10",
-        "base58",
-        "base62",
-        "base64",
-        "base64url",
-        "bitcoin",
-        "flickr",
-        "lowhex",
-        "uphex",
-        "monero",
-        "ripple",
-    ];
+    let charsets = Charset::values()
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>();
+    let mut from_charsets = charsets.iter().map(|c| c.as_str()).collect::<Vec<&str>>();
+    let to_charsets = from_charsets.clone();
+    from_charsets.push("auto");
 
     let matches = App::new("Serious")
     .version("0.1")
@@ -58,39 +52,259 @@ fn main() {
              .short("i")
              .long("input")
              .value_name("INPUT_ENCODING")
-             .help("The input encoding to parse.")
+             .help(&input_help)
              .takes_value(true)
-             .possible_values(inencs.as_slice())
+             .validator(valid_encoding)
              .max_values(1)
              .allow_hyphen_values(true)
-             .required(true))
+             .required_unless("pipeline"))
         .arg(Arg::with_name("output")
             .short("o")
             .long("output")
             .value_name("OUTPUT_ENCODING")
-            .help("The output encoding.")
+            .help(&output_help)
             .takes_value(true)
-            .possible_values(outencs.as_slice())
+            .validator(valid_encoding)
             .max_values(1)
-            .required(true))
+            .required_unless_one(&["pipeline", "extract", "show-mime"]))
+        .arg(Arg::with_name("pipeline")
+            .short("p")
+            .long("pipeline")
+            .value_name("PIPELINE")
+            .help("Stages to run on the input separated by '|' like \"base64 | gunzip | reverse | hex\". When combined with input or output encodings, the input is decoded before the pipeline and the output is encoded after it.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("inflate")
+            .long("inflate")
+            .help("Decompress the decoded input using the compression detected from its magic bytes, falling back to raw deflate.")
+            .takes_value(false)
+            .conflicts_with("decompress")
+            .required(false))
+        .arg(Arg::with_name("decompress")
+            .short("d")
+            .long("decompress")
+            .value_name("COMPRESSION")
+            .help("Decompress the decoded input.")
+            .takes_value(true)
+            .possible_values(compressions.as_slice())
+            .required(false))
+        .arg(Arg::with_name("compress")
+            .short("c")
+            .long("compress")
+            .value_name("COMPRESSION")
+            .help("Compress the output before encoding it.")
+            .takes_value(true)
+            .possible_values(compressions.as_slice())
+            .required(false))
+        .arg(Arg::with_name("from-charset")
+            .long("from-charset")
+            .value_name("CHARSET")
+            .help("The character set of the input text, converted to UTF-8 before decoding. 'auto' detects it from the byte order mark.")
+            .takes_value(true)
+            .possible_values(from_charsets.as_slice())
+            .required(false))
+        .arg(Arg::with_name("to-charset")
+            .long("to-charset")
+            .value_name("CHARSET")
+            .help("The character set to convert the output text to.")
+            .takes_value(true)
+            .possible_values(to_charsets.as_slice())
+            .conflicts_with_all(&["name", "mode"])
+            .required(false))
+        .arg(Arg::with_name("bom")
+            .long("bom")
+            .help("Start the output with a byte order mark when converting it to another character set.")
+            .takes_value(false)
+            .requires("to-charset")
+            .required(false))
+        .arg(Arg::with_name("name")
+            .long("name")
+            .value_name("FILE_NAME")
+            .help("The file name to write in the header of uuencode, yenc or binhex output.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("mode")
+            .long("mode")
+            .value_name("FILE_MODE")
+            .help("The octal file mode to write in the header of uuencode output.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("extract")
+            .short("x")
+            .long("extract")
+            .help("Decode uuencode, yenc or binhex input and write it to the file named in its header.")
+            .takes_value(false)
+            .conflicts_with_all(&["output", "pipeline", "inflate", "decompress", "compress"])
+            .required(false))
+        .arg(Arg::with_name("force")
+            .short("f")
+            .long("force")
+            .help("Overwrite the file named in the header when extracting.")
+            .takes_value(false)
+            .requires("extract")
+            .required(false))
+        .arg(Arg::with_name("mime")
+            .long("mime")
+            .value_name("MIME_TYPE")
+            .help("The media type like \"text/css;charset=utf-8\" to write in datauri output instead of sniffing it from the data.")
+            .takes_value(true)
+            .conflicts_with_all(&["name", "mode", "to-charset"])
+            .required(false))
+        .arg(Arg::with_name("show-mime")
+            .long("show-mime")
+            .help("Print the media type and parameters of datauri input to STDERR.")
+            .takes_value(false)
+            .conflicts_with_all(&["extract", "from-charset"])
+            .required(false))
+        .arg(Arg::with_name("alphabet")
+            .short("a")
+            .long("alphabet")
+            .value_name("ALPHABET")
+            .help("The symbols of a 'custom' input or output encoding like 0123456789abcdefghijklmnopqrstuvwxyz for base36.")
+            .takes_value(true)
+            .validator(|a| Alphabet::new(a).map(|_| ()))
+            .required(false))
         .arg(Arg::with_name("TEXT")
             .help("The input file or text to change serialization. If no input is specified or input is '-', input is received from STDIN")
             .required(true)
             .index(1))
      .get_matches();
 
-    let input_encoding = Code::parse(matches.value_of("input").unwrap()).unwrap();
-    let output_encoding = Code::parse(matches.value_of("output").unwrap()).unwrap();
+    // 'custom' is filled in with the alphabet so it parses like any other encoding
+    let custom = |arg: &str| match (matches.value_of(arg), matches.value_of("alphabet")) {
+        (Some("custom"), Some(a)) => Some(format!("custom:{}", a)),
+        (Some("custom"), None) => {
+            quit("A custom encoding requires an alphabet".to_string());
+            None
+        }
+        (e, _) => e.map(|e| e.to_string()),
+    };
+    let input = custom("input");
+    let output = custom("output");
+
+    let header = if matches.is_present("name") || matches.is_present("mode") {
+        let mut header = FileHeader::default();
+        if let Some(name) = matches.value_of("name") {
+            header.name = name.to_string();
+        }
+        if let Some(mode) = matches.value_of("mode") {
+            match u32::from_str_radix(mode, 8) {
+                Ok(m) => header.mode = m,
+                Err(_) => quit(format!("Invalid octal file mode {}", mode)),
+            }
+        }
+        match output.as_ref().map(Code::parse) {
+            Some(Ok(oe)) if oe.has_file_header() => Some((oe, header)),
+            _ => {
+                quit("A file name or mode requires uuencode, yenc or binhex output".to_string());
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
+    let media = match matches.value_of("mime").map(MediaType::parse) {
+        Some(Ok(m)) if output.as_ref().map(Code::parse) == Some(Ok(DataUri)) => Some(m),
+        Some(Ok(_)) => {
+            quit("A MIME type requires datauri output".to_string());
+            return;
+        }
+        Some(Err(e)) => {
+            quit(e);
+            return;
+        }
+        None => None,
+    };
+    let inspect = matches.is_present("show-mime");
+    if inspect && input.as_ref().map(Code::parse) != Some(Ok(DataUri)) {
+        quit("Showing the MIME type requires datauri input".to_string());
+    }
+
+    let stages = [
+        "pipeline",
+        "inflate",
+        "decompress",
+        "compress",
+        "from-charset",
+        "to-charset",
+    ];
+    let action = if matches.is_present("extract") {
+        match input.as_ref().map(Code::parse) {
+            Some(Ok(ie)) if ie.has_file_header() => {
+                Action::Extract(ie, matches.is_present("force"))
+            }
+            _ => {
+                quit("Extract requires uuencode, yenc or binhex input".to_string());
+                return;
+            }
+        }
+    } else if header.is_some()
+        || media.is_some()
+        || inspect
+        || stages.iter().any(|s| matches.is_present(s))
+    {
+        let mut spec = Vec::new();
+        if let Some(c) = matches.value_of("from-charset") {
+            spec.push(format!("from:{}", c));
+        }
+        match &input {
+            Some(ie) if !inspect => spec.push(format!("decode:{}", ie)),
+            _ => (),
+        }
+        if matches.is_present("inflate") {
+            spec.push("inflate".to_string());
+        }
+        if let Some(c) = matches.value_of("decompress") {
+            spec.push(format!("decompress:{}", c));
+        }
+        if let Some(p) = matches.value_of("pipeline") {
+            spec.push(p.to_string());
+        }
+        if let Some(c) = matches.value_of("compress") {
+            spec.push(format!("compress:{}", c));
+        }
+        match &output {
+            Some(oe) if header.is_none() && media.is_none() => spec.push(format!("encode:{}", oe)),
+            _ => (),
+        }
+        if let Some(c) = matches.value_of("to-charset") {
+            match matches.is_present("bom") {
+                true => spec.push(format!("to:{}+bom", c)),
+                false => spec.push(format!("to:{}", c)),
+            }
+        }
+        // An empty spec happens with only --show-mime and leaves the data as is
+        let pipeline = match spec.is_empty() {
+            true => Ok(Pipeline::new()),
+            false => Pipeline::parse(spec.join(" | ")),
+        };
+        match (pipeline, header, media) {
+            (Ok(p), Some((oe, header)), _) => Action::Wrap(p, oe, header),
+            (Ok(p), None, Some(media)) => Action::Embed(p, media),
+            (Ok(p), None, None) if inspect => Action::Inspect(p),
+            (Ok(p), None, None) => Action::Pipeline(p),
+            (Err(e), _, _) => {
+                quit(e);
+                return;
+            }
+        }
+    } else {
+        Action::Recode(
+            Code::parse(input.unwrap()).unwrap(),
+            Code::parse(output.unwrap()).unwrap(),
+        )
+    };
     match matches.value_of("TEXT") {
         Some(text) => {
             if text == "-" {
                 let mut f = io::stdin();
-                recode_stream(&mut f, input_encoding, output_encoding);
+                recode_stream(&mut f, &action);
             } else {
                 match get_file(text) {
                     Some(file) => {
                         match File::open(file.as_path()) {
-                            Ok(mut f) => recode_stream(&mut f, input_encoding, output_encoding),
+                            Ok(mut f) => recode_stream(&mut f, &action),
                             Err(_) => {
                                 quit(format!("Unable to read file {}", file.to_str().unwrap()));
                             }
@@ -98,19 +312,32 @@ fn main() {
                     }
                     None => {
                         let mut f = StringReader::new(text);
-                        recode_stream(&mut f, input_encoding, output_encoding);
+                        recode_stream(&mut f, &action);
                     }
                 }
             }
         }
         None => {
             let mut f = io::stdin();
-            recode_stream(&mut f, input_encoding, output_encoding);
+            recode_stream(&mut f, &action);
         }
     };
 }
 
-fn recode_stream<R: Read>(f: &mut R, ie: Code, oe: Code) {
+enum Action {
+    Recode(Code, Code),
+    Pipeline(Pipeline),
+    /// Run the pipeline then encode with a file header
+    Wrap(Pipeline, Code, FileHeader),
+    /// Decode and write the file named in the header, replacing it if forced
+    Extract(Code, bool),
+    /// Run the pipeline then encode as a data URI with the media type
+    Embed(Pipeline, MediaType),
+    /// Decode a data URI, report its media type then run the pipeline
+    Inspect(Pipeline),
+}
+
+fn recode_stream<R: Read>(f: &mut R, action: &Action) {
     let mut out_hash = Vec::new();
 
     let mut buffer = [0u8; 65536];
@@ -123,14 +350,111 @@ fn recode_stream<R: Read>(f: &mut R, ie: Code, oe: Code) {
             break;
         }
 
-        out_hash.write(&buffer[..n]).unwrap();
+        out_hash.write_all(&buffer[..n]).unwrap();
         read = f.read(&mut buffer);
     }
 
-    let res = String::from_utf8(out_hash).unwrap();
-    println!("{}", Code::recode(res, ie, oe).unwrap());
+    match action {
+        Action::Recode(Blob, Blob) => {
+            let res = String::from_utf8(out_hash).unwrap();
+            println!("{}", Code::recode(res, Blob, Blob).unwrap());
+        }
+        Action::Recode(ie, oe) => match Code::decode_bytes(out_hash, *ie) {
+            Ok(res) => match Code::encode(res, *oe).try_into_vec() {
+                Ok(res) => write_line(res),
+                Err(e) => quit(e),
+            },
+            Err(e) => quit(e),
+        },
+        Action::Pipeline(p) => match p.run(out_hash) {
+            Ok(res) => write_output(p, res),
+            Err(e) => quit(e),
+        },
+        Action::Embed(p, media) => match p.run(out_hash) {
+            Ok(res) => write_line(Code::encode_data_uri(res, media).into_bytes()),
+            Err(e) => quit(e),
+        },
+        Action::Inspect(p) => {
+            match Code::decode_data_uri(String::from_utf8_lossy(&out_hash)).and_then(
+                |(media, res)| {
+                    eprintln!("{}", media.mime);
+                    for (k, v) in &media.params {
+                        eprintln!("{}={}", k, v);
+                    }
+                    p.run(res)
+                },
+            ) {
+                Ok(res) => write_output(p, res),
+                Err(e) => quit(e),
+            }
+        }
+        Action::Wrap(p, oe, header) => {
+            match p
+                .run(out_hash)
+                .and_then(|res| Code::encode_file(res, *oe, header))
+            {
+                Ok(res) => write_line(res),
+                Err(e) => quit(e),
+            }
+        }
+        Action::Extract(ie, force) => match Code::decode_file(out_hash, *ie) {
+            Ok((header, res)) => {
+                // Never write outside the current directory
+                let name = PathBuf::from(&header.name);
+                let name = match name.file_name() {
+                    Some(n) => PathBuf::from(n),
+                    None => {
+                        quit(format!("Invalid file name {}", header.name));
+                        return;
+                    }
+                };
+                let file = match force {
+                    true => File::create(&name),
+                    false => OpenOptions::new().write(true).create_new(true).open(&name),
+                };
+                if let Err(e) = file.and_then(|mut f| f.write_all(&res)) {
+                    quit(format!("Unable to write file {} - {}", name.display(), e));
+                }
+                set_mode(&name, header.mode);
+                println!("{} {:o}", name.display(), header.mode);
+            }
+            Err(e) => quit(e),
+        },
+    }
+}
+
+fn valid_encoding(e: String) -> Result<(), String> {
+    match e.as_str() {
+        "custom" => Ok(()),
+        e => Code::parse(e).map(|_| ()),
+    }
 }
 
+fn write_output(p: &Pipeline, data: Vec<u8>) {
+    if p.is_text_output() {
+        println!("{}", String::from_utf8_lossy(&data));
+    } else {
+        io::stdout().write_all(data.as_slice()).unwrap();
+        io::stdout().flush().unwrap();
+    }
+}
+
+fn write_line(data: Vec<u8>) {
+    let mut out = io::stdout();
+    out.write_all(data.as_slice()).unwrap();
+    out.write_all(b"\n").unwrap();
+    out.flush().unwrap();
+}
+
+#[cfg(unix)]
+fn set_mode(file: &PathBuf, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = std::fs::set_permissions(file, std::fs::Permissions::from_mode(mode & 0o777));
+}
+
+#[cfg(not(unix))]
+fn set_mode(_file: &PathBuf, _mode: u32) {}
+
 fn get_file(name: &str) -> Option<PathBuf> {
     let mut file = PathBuf::new();
     file.push(name);
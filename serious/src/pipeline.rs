@@ -0,0 +1,328 @@
+use crate::{Charset, Compression, Encoding};
+use std::fmt::Display;
+
+/// A single step in a [`Pipeline`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Stage {
+    /// Parse encoded text into raw bytes
+    Decode(Encoding),
+    /// Serialize raw bytes into an encoding
+    Encode(Encoding),
+    Compress(Compression),
+    Decompress(Compression),
+    /// Decompress with the compression detected from magic bytes
+    Inflate,
+    /// Convert text in a character set to UTF-8, detecting it from the
+    /// byte order mark and defaulting to UTF-8 when there is none
+    FromCharset(Option<Charset>),
+    /// Convert UTF-8 text to a character set, with or without a byte order mark
+    ToCharset(Charset, bool),
+    /// Reverse the order of all bytes
+    Reverse,
+    /// Reverse the byte order of each word of the given width
+    Swap(usize),
+}
+
+use Stage::*;
+
+impl Stage {
+    pub fn apply<T: AsRef<[u8]>>(&self, data: T) -> Result<Vec<u8>, String> {
+        let data = data.as_ref();
+        match *self {
+            Decode(Encoding::Blob) => Ok(data.to_vec()),
+            Decode(e) => {
+                let end = data
+                    .iter()
+                    .rposition(|b| *b != b'\n' && *b != b'\r')
+                    .map_or(0, |i| i + 1);
+                Encoding::decode_bytes(&data[..end], e)
+            }
+            Encode(e) => Encoding::encode(data, e).try_into_vec(),
+            Compress(c) => Compression::compress(data, c),
+            Decompress(c) => Compression::decompress(data, c),
+            Inflate => Compression::inflate(data),
+            FromCharset(c) => {
+                let c = c.or_else(|| Charset::detect(data)).unwrap_or(Charset::Utf8);
+                Charset::transcode(data, c, Charset::Utf8, false)
+            }
+            ToCharset(c, bom) => {
+                let s = std::str::from_utf8(data)
+                    .map_err(|_| format!("Input to {} is not valid UTF-8", self))?;
+                Charset::encode(s, c, bom)
+            }
+            Reverse => {
+                let mut out = data.to_vec();
+                out.reverse();
+                Ok(out)
+            }
+            Swap(width) => {
+                if data.len() % width != 0 {
+                    return Err(format!(
+                        "Input length {} is not a multiple of {} for {}",
+                        data.len(),
+                        width,
+                        self
+                    ));
+                }
+                let mut out = data.to_vec();
+                out.chunks_mut(width).for_each(|c| c.reverse());
+                Ok(out)
+            }
+        }
+    }
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Decode(e) => write!(f, "decode:{}", e),
+            Encode(e) => write!(f, "encode:{}", e),
+            Compress(c) => write!(f, "{}", c),
+            Decompress(Compression::Gzip) => write!(f, "gunzip"),
+            Decompress(c) => write!(f, "un{}", c),
+            Inflate => write!(f, "inflate"),
+            FromCharset(Some(c)) => write!(f, "from:{}", c),
+            FromCharset(None) => write!(f, "from:auto"),
+            ToCharset(c, false) => write!(f, "to:{}", c),
+            ToCharset(c, true) => write!(f, "to:{}+bom", c),
+            Reverse => write!(f, "reverse"),
+            Swap(width) => write!(f, "swap{}", width * 8),
+        }
+    }
+}
+
+/// A chain of stages separated by `|` like `base64 | gunzip | reverse | hex`.
+///
+/// Stages can be written explicitly as `decode:<encoding>`, `encode:<encoding>`,
+/// `compress:<compression>` and `decompress:<compression>`. A bare compression name
+/// compresses, `un<compression>` or `gunzip` decompresses and `inflate` decompresses
+/// whatever compression is detected. Text is converted between character sets with
+/// `from:<charset>` or `from:auto` to UTF-8 and `to:<charset>` or `to:<charset>+bom` from UTF-8.
+///
+/// A bare encoding name decodes when the data is still encoded text and encodes when
+/// the data is raw bytes. Data starts as encoded text if the first stage is an encoding
+/// and becomes raw bytes after any other stage except character set conversions.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse<I: AsRef<str>>(s: I) -> Result<Self, String> {
+        let mut stages = Vec::new();
+        let mut encoded = true;
+        for token in s.as_ref().split('|').map(|t| t.trim()) {
+            if token.is_empty() {
+                return Err("Empty pipeline stage".to_string());
+            }
+            let stage = match token.split_once(':') {
+                Some(("decode", e)) => Decode(Encoding::parse(e)?),
+                Some(("encode", e)) => Encode(Encoding::parse(e)?),
+                Some(("compress", c)) => Compress(Compression::parse(c)?),
+                Some(("decompress", c)) => Decompress(Compression::parse(c)?),
+                Some(("from", "auto")) => FromCharset(None),
+                Some(("from", c)) => FromCharset(Some(Charset::parse(c)?)),
+                Some(("to", c)) => match c.strip_suffix("+bom") {
+                    Some(c) => ToCharset(Charset::parse(c)?, true),
+                    None => ToCharset(Charset::parse(c)?, false),
+                },
+                Some(("custom" | "radix" | "eip1191", _)) if encoded => {
+                    Decode(Encoding::parse(token)?)
+                }
+                Some(("custom" | "radix" | "eip1191", _)) => Encode(Encoding::parse(token)?),
+                Some(_) => return Err(format!("Unknown pipeline stage: {}", token)),
+                None => match token {
+                    "gunzip" => Decompress(Compression::Gzip),
+                    "inflate" => Inflate,
+                    "reverse" => Reverse,
+                    "swap16" => Swap(2),
+                    "swap32" => Swap(4),
+                    "swap64" => Swap(8),
+                    _ => {
+                        if let Ok(c) = Compression::parse(token) {
+                            Compress(c)
+                        } else if let Some(Ok(c)) = token.strip_prefix("un").map(Compression::parse)
+                        {
+                            Decompress(c)
+                        } else {
+                            match Encoding::parse(token) {
+                                Ok(e) if encoded => Decode(e),
+                                Ok(e) => Encode(e),
+                                Err(_) => return Err(format!("Unknown pipeline stage: {}", token)),
+                            }
+                        }
+                    }
+                },
+            };
+            encoded = match stage {
+                Encode(_) => true,
+                FromCharset(_) | ToCharset(..) => encoded,
+                _ => false,
+            };
+            stages.push(stage);
+        }
+        Ok(Pipeline { stages })
+    }
+
+    pub fn push(&mut self, stage: Stage) {
+        self.stages.push(stage);
+    }
+
+    pub fn stages(&self) -> &[Stage] {
+        self.stages.as_slice()
+    }
+
+    /// True when the final stage produces encoded text instead of raw bytes
+    pub fn is_text_output(&self) -> bool {
+        matches!(self.stages.last(), Some(Encode(e)) if e.is_text())
+    }
+
+    pub fn run<T: AsRef<[u8]>>(&self, input: T) -> Result<Vec<u8>, String> {
+        let mut data = input.as_ref().to_vec();
+        for stage in &self.stages {
+            data = stage.apply(data)?;
+        }
+        Ok(data)
+    }
+}
+
+impl Display for Pipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let stages = self
+            .stages
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "{}", stages.join(" | "))
+    }
+}
+
+impl From<Vec<Stage>> for Pipeline {
+    fn from(stages: Vec<Stage>) -> Self {
+        Pipeline { stages }
+    }
+}
+
+/// Whether `word` is a pipeline stage other than an encoding, or the start of
+/// a `<stage>:` token, so an encoding with that name could never be reached
+pub(crate) fn is_keyword(word: &str) -> bool {
+    matches!(
+        word,
+        "gunzip"
+            | "inflate"
+            | "reverse"
+            | "swap16"
+            | "swap32"
+            | "swap64"
+            | "decode"
+            | "encode"
+            | "compress"
+            | "decompress"
+            | "from"
+            | "to"
+    ) || Compression::parse(word).is_ok()
+        || word
+            .strip_prefix("un")
+            .is_some_and(|c| Compression::parse(c).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn parse() {
+        let p = Pipeline::parse("base64 | gunzip | reverse | hex").unwrap();
+        assert_eq!(
+            p.stages(),
+            &[
+                Decode(Encoding::Base64),
+                Decompress(Compression::Gzip),
+                Reverse,
+                Encode(Encoding::LowHex)
+            ]
+        );
+        assert!(p.is_text_output());
+        assert_eq!(Pipeline::parse(p.to_string()).unwrap(), p);
+
+        let p = Pipeline::parse("gzip | base64").unwrap();
+        assert_eq!(
+            p.stages(),
+            &[Compress(Compression::Gzip), Encode(Encoding::Base64)]
+        );
+        let p = Pipeline::parse("hex|base58").unwrap();
+        assert_eq!(
+            p.stages(),
+            &[Decode(Encoding::LowHex), Encode(Encoding::Base58)]
+        );
+
+        let p = Pipeline::parse("base64 | inflate | unzstd | xz | blob").unwrap();
+        assert_eq!(
+            p.stages(),
+            &[
+                Decode(Encoding::Base64),
+                Inflate,
+                Decompress(Compression::Zstd),
+                Compress(Compression::Xz),
+                Encode(Encoding::Blob)
+            ]
+        );
+        assert!(!p.is_text_output());
+        assert_eq!(Pipeline::parse(p.to_string()).unwrap(), p);
+
+        let p = Pipeline::parse("from:auto | hex | base64 | to:utf-16le+bom").unwrap();
+        assert_eq!(
+            p.stages(),
+            &[
+                FromCharset(None),
+                Decode(Encoding::LowHex),
+                Encode(Encoding::Base64),
+                ToCharset(Charset::Utf16Le, true)
+            ]
+        );
+        assert_eq!(Pipeline::parse(p.to_string()).unwrap(), p);
+
+        let p = Pipeline::parse("hex | custom:01").unwrap();
+        assert_eq!(p.run("05").unwrap(), b"101");
+        let p = Pipeline::parse("radix:8:bytes | radix:7").unwrap();
+        assert_eq!(p.run("377").unwrap(), b"513");
+        assert_eq!(Pipeline::parse(p.to_string()).unwrap(), p);
+
+        assert!(Pipeline::parse("hex | | base58").is_err());
+        assert!(Pipeline::parse("hex | nope").is_err());
+        assert!(Pipeline::parse("decode:nope").is_err());
+    }
+
+    #[test]
+    fn run() {
+        let mut rng = rand::thread_rng();
+        let mut buffer = vec![0u8; 64];
+        rng.fill_bytes(buffer.as_mut_slice());
+
+        let there = Pipeline::parse("gzip | base64").unwrap();
+        let back = Pipeline::parse("base64 | gunzip | reverse | hex").unwrap();
+        let res = back.run(there.run(&buffer).unwrap()).unwrap();
+        buffer.reverse();
+        assert_eq!(hex::encode(&buffer), String::from_utf8(res).unwrap());
+
+        for c in Compression::values() {
+            let p = Pipeline::parse(format!("{} | un{}", c, c)).unwrap();
+            assert_eq!(buffer, p.run(&buffer).unwrap());
+        }
+
+        let p = Pipeline::parse("from:auto | hex | blob | to:utf-16be").unwrap();
+        assert_eq!(
+            p.run(b"\xFF\xFE6\x008\x006\x009\x00").unwrap(),
+            b"\x00h\x00i"
+        );
+
+        let p = Pipeline::parse("hex | swap32 | hex").unwrap();
+        assert_eq!(p.run("0102030405060708\n").unwrap(), b"0403020108070605");
+        assert!(p.run("010203").is_err());
+    }
+}
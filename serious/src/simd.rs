@@ -0,0 +1,302 @@
+use std::fmt::Display;
+
+#[cfg(feature = "simd")]
+pub(crate) use vector::*;
+
+#[cfg(not(feature = "simd"))]
+pub(crate) use scalar::*;
+
+/// The instruction sets `faster-hex` and `base64-simd` pick on this CPU. They
+/// detect features differently so hex and base64 can use different ones.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SimdBackend {
+    /// One of `avx2`, `sse4.1`, `neon` or `scalar`
+    pub hex: &'static str,
+    /// One of `avx2`, `ssse3`, `neon` or `scalar`
+    pub base64: &'static str,
+}
+
+impl Display for SimdBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "hex {}, base64 {}", self.hex, self.base64)
+    }
+}
+
+/// Mirrors the runtime dispatch in `faster-hex` and `base64-simd`, which don't expose it
+pub fn simd_backend() -> SimdBackend {
+    #[allow(unused_mut)]
+    let mut backend = SimdBackend {
+        hex: "scalar",
+        base64: "scalar",
+    };
+    #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        if std::arch::is_x86_feature_detected!("avx2") {
+            backend.hex = "avx2";
+        } else if std::arch::is_x86_feature_detected!("sse4.1") {
+            backend.hex = "sse4.1";
+        }
+        if std::arch::is_x86_feature_detected!("avx2") {
+            backend.base64 = "avx2";
+        } else if std::arch::is_x86_feature_detected!("ssse3") {
+            backend.base64 = "ssse3";
+        }
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            backend.hex = "neon";
+            backend.base64 = "neon";
+        }
+    }
+    backend
+}
+
+/// The `hex` and `base64` crates, used when SIMD is disabled and as the
+/// reference the vector implementations must match
+#[cfg_attr(feature = "simd", allow(dead_code))]
+pub(crate) mod scalar {
+    use base64_url::base64::{
+        alphabet,
+        engine::{
+            general_purpose::URL_SAFE_NO_PAD, DecodePaddingMode, GeneralPurpose,
+            GeneralPurposeConfig,
+        },
+        Engine,
+    };
+
+    /// Padded standard base64 for data URIs. It decodes with or without padding.
+    const BASE64: GeneralPurpose = GeneralPurpose::new(
+        &alphabet::STANDARD,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+
+    pub(crate) fn hex_encode(data: &[u8], upper: bool) -> String {
+        match upper {
+            true => hex::encode_upper(data),
+            false => hex::encode(data),
+        }
+    }
+
+    /// `out` must be exactly twice the length of `data`
+    pub(crate) fn hex_encode_slice(data: &[u8], out: &mut [u8], upper: bool) -> Result<(), String> {
+        hex::encode_to_slice(data, out).map_err(|e| e.to_string())?;
+        if upper {
+            out.make_ascii_uppercase();
+        }
+        Ok(())
+    }
+
+    pub(crate) fn hex_decode(data: &[u8]) -> Result<Vec<u8>, String> {
+        hex::decode(data).map_err(|e| e.to_string())
+    }
+
+    /// `out` must be exactly half the length of `data`
+    pub(crate) fn hex_decode_slice(data: &[u8], out: &mut [u8]) -> Result<(), String> {
+        hex::decode_to_slice(data, out).map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn base64_encode(data: &[u8], url: bool) -> String {
+        match url {
+            true => URL_SAFE_NO_PAD.encode(data),
+            false => BASE64.encode(data),
+        }
+    }
+
+    pub(crate) fn base64_encode_slice(
+        data: &[u8],
+        out: &mut [u8],
+        url: bool,
+    ) -> Result<usize, String> {
+        match url {
+            true => URL_SAFE_NO_PAD.encode_slice(data, out),
+            false => BASE64.encode_slice(data, out),
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn base64_decode(data: &[u8], url: bool) -> Result<Vec<u8>, String> {
+        match url {
+            true => URL_SAFE_NO_PAD.decode(data),
+            false => BASE64.decode(data),
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn base64_decode_slice(
+        data: &[u8],
+        out: &mut [u8],
+        url: bool,
+    ) -> Result<usize, String> {
+        match url {
+            true => URL_SAFE_NO_PAD.decode_slice(data, out),
+            false => BASE64.decode_slice(data, out),
+        }
+        .map_err(|e| e.to_string())
+    }
+}
+
+/// SSSE3, AVX2 and NEON implementations picked at runtime by `faster-hex` and
+/// `base64-simd`. They fall back to scalar code on other CPUs. Errors are rare
+/// so failures are rerun through [`scalar`] to report them with the same messages.
+#[cfg(feature = "simd")]
+mod vector {
+    use super::scalar;
+    use base64_simd::{Base64, Out, STANDARD, STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+
+    fn encoder(url: bool) -> &'static Base64 {
+        match url {
+            true => &URL_SAFE_NO_PAD,
+            false => &STANDARD,
+        }
+    }
+
+    /// Standard base64 decodes input with or without padding
+    fn decoder(data: &[u8], url: bool) -> &'static Base64 {
+        match (url, data.len().is_multiple_of(4)) {
+            (true, _) => &URL_SAFE_NO_PAD,
+            (false, true) => &STANDARD,
+            (false, false) => &STANDARD_NO_PAD,
+        }
+    }
+
+    pub(crate) fn hex_encode(data: &[u8], upper: bool) -> String {
+        match upper {
+            true => faster_hex::hex_string_upper(data),
+            false => faster_hex::hex_string(data),
+        }
+    }
+
+    pub(crate) fn hex_encode_slice(data: &[u8], out: &mut [u8], upper: bool) -> Result<(), String> {
+        if out.len() != data.len() * 2 {
+            return scalar::hex_encode_slice(data, out, upper);
+        }
+        match upper {
+            true => faster_hex::hex_encode_upper(data, out),
+            false => faster_hex::hex_encode(data, out),
+        }
+        .map(|_| ())
+        .or_else(|_| scalar::hex_encode_slice(data, out, upper))
+    }
+
+    pub(crate) fn hex_decode(data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = vec![0u8; data.len() / 2];
+        hex_decode_slice(data, &mut out).map(|_| out)
+    }
+
+    pub(crate) fn hex_decode_slice(data: &[u8], out: &mut [u8]) -> Result<(), String> {
+        if !data.len().is_multiple_of(2) || out.len() != data.len() / 2 {
+            return scalar::hex_decode_slice(data, out);
+        }
+        faster_hex::hex_decode(data, out).or_else(|_| scalar::hex_decode_slice(data, out))
+    }
+
+    pub(crate) fn base64_encode(data: &[u8], url: bool) -> String {
+        encoder(url).encode_to_string(data)
+    }
+
+    pub(crate) fn base64_encode_slice(
+        data: &[u8],
+        out: &mut [u8],
+        url: bool,
+    ) -> Result<usize, String> {
+        let base64 = encoder(url);
+        if out.len() < base64.encoded_length(data.len()) {
+            return scalar::base64_encode_slice(data, out, url);
+        }
+        Ok(base64.encode(data, Out::from_slice(out)).len())
+    }
+
+    pub(crate) fn base64_decode(data: &[u8], url: bool) -> Result<Vec<u8>, String> {
+        decoder(data, url)
+            .decode_to_vec(data)
+            .or_else(|_| scalar::base64_decode(data, url))
+    }
+
+    pub(crate) fn base64_decode_slice(
+        data: &[u8],
+        out: &mut [u8],
+        url: bool,
+    ) -> Result<usize, String> {
+        let base64 = decoder(data, url);
+        match base64.decoded_length(data) {
+            Ok(n) if n <= out.len() => base64
+                .decode(data, Out::from_slice(out))
+                .map(|o| o.len())
+                .or_else(|_| scalar::base64_decode_slice(data, out, url)),
+            _ => scalar::base64_decode_slice(data, out, url),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "simd"))]
+mod tests {
+    use super::{scalar, vector};
+    use rand::{Rng, RngCore};
+
+    #[test]
+    fn matches_scalar() {
+        let mut rng = rand::thread_rng();
+        for n in 0..200 {
+            let mut buffer = vec![0u8; n];
+            rng.fill_bytes(buffer.as_mut_slice());
+            for upper in [false, true] {
+                let encoded = vector::hex_encode(&buffer, upper);
+                assert_eq!(encoded, scalar::hex_encode(&buffer, upper));
+                assert_eq!(vector::hex_decode(encoded.as_bytes()).unwrap(), buffer);
+                let mut out = vec![0u8; n * 2];
+                vector::hex_encode_slice(&buffer, &mut out, upper).unwrap();
+                assert_eq!(out, encoded.as_bytes());
+            }
+            for url in [false, true] {
+                let encoded = vector::base64_encode(&buffer, url);
+                assert_eq!(encoded, scalar::base64_encode(&buffer, url));
+                assert_eq!(
+                    vector::base64_decode(encoded.as_bytes(), url).unwrap(),
+                    buffer
+                );
+                let mut out = vec![0u8; encoded.len()];
+                let len = vector::base64_encode_slice(&buffer, &mut out, url).unwrap();
+                assert_eq!(&out[..len], encoded.as_bytes());
+                let mut back = vec![0u8; n];
+                let len = vector::base64_decode_slice(encoded.as_bytes(), &mut back, url).unwrap();
+                assert_eq!(&back[..len], buffer.as_slice());
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_like_scalar() {
+        const NOISE: &[u8] = b"0aAfFgG+/-_=\n \xff";
+        let mut rng = rand::thread_rng();
+        for n in 0..500 {
+            let mut buffer = vec![0u8; n % 70];
+            rng.fill_bytes(buffer.as_mut_slice());
+            let mut hex = scalar::hex_encode(&buffer, n % 2 == 0).into_bytes();
+            let mut b64 = scalar::base64_encode(&buffer, false).into_bytes();
+            let mut url = scalar::base64_encode(&buffer, true).into_bytes();
+            for s in [&mut hex, &mut b64, &mut url] {
+                if !s.is_empty() {
+                    let i = rng.gen_range(0..s.len());
+                    s[i] = NOISE[rng.gen_range(0..NOISE.len())];
+                }
+                if n % 7 == 0 {
+                    s.push(NOISE[rng.gen_range(0..NOISE.len())]);
+                }
+            }
+            assert_eq!(vector::hex_decode(&hex), scalar::hex_decode(&hex));
+            for (s, url) in [(&b64, false), (&url, true)] {
+                assert_eq!(vector::base64_decode(s, url), scalar::base64_decode(s, url));
+            }
+        }
+        // Non-zero trailing bits and misplaced padding
+        for s in ["QR==", "QR", "QUJ=", "QQ=", "Q===", "QQ==QQ=="] {
+            assert_eq!(
+                vector::base64_decode(s.as_bytes(), false),
+                scalar::base64_decode(s.as_bytes(), false),
+                "{}",
+                s
+            );
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{radix_width, simd, Encoding, Encoding::*};
+
+const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+fn too_small(have: usize, need: usize) -> String {
+    format!(
+        "Output buffer of {} bytes is too small, {} bytes are needed",
+        have, need
+    )
+}
+
+fn copy_into(data: &[u8], out: &mut [u8]) -> Result<usize, String> {
+    match out.get_mut(..data.len()) {
+        Some(o) => {
+            o.copy_from_slice(data);
+            Ok(data.len())
+        }
+        None => Err(too_small(out.len(), data.len())),
+    }
+}
+
+impl Encoding {
+    /// The length of the encoded form of `input_len` bytes. This is exact for
+    /// fixed ratio encodings like hex, base64 and `radix:<n>:bytes` and an
+    /// upper bound for the rest. `None` when no bound can be computed.
+    pub fn encoded_len(&self, input_len: usize) -> Option<usize> {
+        let n = input_len;
+        match *self {
+            Eip1191Hex(_) => n.checked_mul(2)?.checked_add(2),
+            RadixBytes(r) => n.checked_mul(radix_width(r)),
+            // A big integer never has more digits than writing each byte separately
+            Radix(r) => n.checked_mul(radix_width(r)).map(|l| l.max(1)),
+            // Symbols may take more than one byte in UTF-8
+            Custom(a) => n
+                .checked_mul(radix_width(a.len() as u32))?
+                .checked_mul(a.symbols().chars().map(char::len_utf8).max()?),
+            Registered(_) => None,
+            e => (e.builtin().encoded_len)(n),
+        }
+    }
+
+    /// An upper bound on the number of bytes decoded from `encoded_len` bytes of
+    /// encoded input. `None` when no bound can be computed.
+    pub fn decoded_len_estimate(&self, encoded_len: usize) -> Option<usize> {
+        let n = encoded_len;
+        match *self {
+            Eip1191Hex(_) => Some(n / 2),
+            RadixBytes(r) => Some(n / radix_width(r)),
+            Radix(_) | Custom(_) => Some(n),
+            Registered(_) => None,
+            e => (e.builtin().decoded_len)(n),
+        }
+    }
+
+    /// Encode into `out` returning the number of bytes written. Blob, hex, base64
+    /// and `radix:<n>:bytes` don't allocate, other encodings are encoded then copied.
+    pub fn encode_to_slice<T: AsRef<[u8]>>(
+        data: T,
+        tgt: Self,
+        out: &mut [u8],
+    ) -> Result<usize, String> {
+        let data = data.as_ref();
+        let need = tgt.encoded_len(data.len());
+        if let Some(need) = need.filter(|need| *need > out.len()) {
+            // Bounds for variable length encodings may be too big so only exact ones fail early
+            if matches!(
+                tgt,
+                Blob | LowHex | UpHex | Base64 | Base64Url | RadixBytes(_)
+            ) {
+                return Err(too_small(out.len(), need));
+            }
+        }
+        match tgt {
+            Blob => copy_into(data, out),
+            LowHex | UpHex => {
+                let out = &mut out[..data.len() * 2];
+                simd::hex_encode_slice(data, out, tgt == UpHex)?;
+                Ok(out.len())
+            }
+            Base64 | Base64Url => simd::base64_encode_slice(data, out, true),
+            RadixBytes(r) => {
+                let width = radix_width(r);
+                for (b, o) in data.iter().zip(out.chunks_mut(width)) {
+                    let mut v = u32::from(*b);
+                    for d in o.iter_mut().rev() {
+                        *d = DIGITS[(v % r) as usize];
+                        v /= r;
+                    }
+                }
+                Ok(data.len() * width)
+            }
+            e => copy_into(&Encoding::encode(data, e).try_into_vec()?, out),
+        }
+    }
+
+    /// Decode into `out` returning the number of bytes written. Blob, hex, base64
+    /// and `radix:<n>:bytes` don't allocate, other encodings are decoded then copied.
+    pub fn decode_to_slice<T: AsRef<[u8]>>(
+        data: T,
+        src: Self,
+        out: &mut [u8],
+    ) -> Result<usize, String> {
+        let data = data.as_ref();
+        match src {
+            Blob => copy_into(data, out),
+            LowHex | UpHex => {
+                if !data.len().is_multiple_of(2) {
+                    return Err(hex::FromHexError::OddLength.to_string());
+                }
+                let need = data.len() / 2;
+                if need > out.len() {
+                    return Err(too_small(out.len(), need));
+                }
+                simd::hex_decode_slice(data, &mut out[..need])?;
+                Ok(need)
+            }
+            Base64 | Base64Url => simd::base64_decode_slice(data, out, true),
+            RadixBytes(r) => {
+                let width = radix_width(r);
+                let mut digits = data.iter().filter(|b| !b.is_ascii_whitespace());
+                let mut written = 0;
+                loop {
+                    let mut v = 0u32;
+                    for i in 0..width {
+                        match digits.next().map(|d| (*d as char).to_digit(r)) {
+                            None if i == 0 => return Ok(written),
+                            None => {
+                                return Err(format!(
+                                    "{} needs {} digits for every byte",
+                                    src, width
+                                ))
+                            }
+                            Some(None) => return Err(format!("Invalid {} digit", src)),
+                            Some(Some(d)) => v = v * r + d,
+                        }
+                    }
+                    let b = u8::try_from(v).map_err(|_| format!("Invalid {} byte", src))?;
+                    *out.get_mut(written)
+                        .ok_or_else(|| too_small(written, written + 1))? = b;
+                    written += 1;
+                }
+            }
+            e => copy_into(&Encoding::decode_bytes(data, e)?, out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn lengths() {
+        let mut rng = rand::thread_rng();
+        for n in [0, 1, 2, 3, 16, 31, 32, 64, 100] {
+            let mut buffer = vec![0u8; n];
+            rng.fill_bytes(buffer.as_mut_slice());
+            let mut values = Encoding::values();
+            values.extend([Radix(7), RadixBytes(3), Custom(crate::Alphabet::BASE36)]);
+            for e in values {
+                let encoded = match Encoding::encode(&buffer, e).try_into_vec() {
+                    Ok(v) => v,
+                    Err(_) => continue,
+                };
+                if let Some(len) = e.encoded_len(n) {
+                    assert!(len >= encoded.len(), "{} {}", e, n);
+                }
+                if let Some(len) = e.decoded_len_estimate(encoded.len()) {
+                    let decoded = Encoding::decode_bytes(&encoded, e).unwrap();
+                    assert!(len >= decoded.len(), "{} {}", e, n);
+                }
+            }
+            for e in [
+                LowHex,
+                UpHex,
+                Base64,
+                Base64Url,
+                RadixBytes(8),
+                Blob,
+                Uuencode,
+            ] {
+                assert_eq!(
+                    e.encoded_len(n).unwrap(),
+                    Encoding::encode(&buffer, e).into_vec().len()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn slices() {
+        let mut rng = rand::thread_rng();
+        let mut buffer = vec![0u8; 64];
+        rng.fill_bytes(buffer.as_mut_slice());
+        let mut out = [0u8; 1024];
+        let mut back = [0u8; 1024];
+        for e in [
+            Blob,
+            LowHex,
+            UpHex,
+            Base64,
+            Base64Url,
+            RadixBytes(8),
+            RadixBytes(36),
+            Base58,
+            Percent,
+        ] {
+            let len = Encoding::encode_to_slice(&buffer, e, &mut out).unwrap();
+            assert_eq!(
+                &out[..len],
+                Encoding::encode(&buffer, e).into_vec().as_slice()
+            );
+            let len = Encoding::decode_to_slice(&out[..len], e, &mut back).unwrap();
+            assert_eq!(&back[..len], buffer.as_slice());
+        }
+
+        let mut small = [0u8; 127];
+        assert!(Encoding::encode_to_slice(&buffer, LowHex, &mut small).is_err());
+        assert!(Encoding::encode_to_slice(&buffer, Base58, &mut [0u8; 10]).is_err());
+        assert!(Encoding::decode_to_slice("00ff", LowHex, &mut [0u8; 1]).is_err());
+        assert!(Encoding::decode_to_slice("101 17", RadixBytes(8), &mut back).is_err());
+        assert_eq!(
+            Encoding::decode_to_slice("101 172", RadixBytes(8), &mut back).unwrap(),
+            2
+        );
+    }
+}
@@ -0,0 +1,381 @@
+const PROQUINT_CONSONANTS: &[u8; 16] = b"bdfghjklmnprstvz";
+const PROQUINT_VOWELS: &[u8; 4] = b"aiou";
+
+/// PGP word list, the even (two syllable) and odd (three syllable) word for each byte
+const PGP_WORDS: [(&str, &str); 256] = [
+    ("aardvark", "adroitness"),
+    ("absurd", "adviser"),
+    ("accrue", "aftermath"),
+    ("acme", "aggregate"),
+    ("adrift", "alkali"),
+    ("adult", "almighty"),
+    ("afflict", "amulet"),
+    ("ahead", "amusement"),
+    ("aimless", "antenna"),
+    ("Algol", "applicant"),
+    ("allow", "Apollo"),
+    ("alone", "armistice"),
+    ("ammo", "article"),
+    ("ancient", "asteroid"),
+    ("apple", "Atlantic"),
+    ("artist", "atmosphere"),
+    ("assume", "autopsy"),
+    ("Athens", "Babylon"),
+    ("atlas", "backwater"),
+    ("Aztec", "barbecue"),
+    ("baboon", "belowground"),
+    ("backfield", "bifocals"),
+    ("backward", "bodyguard"),
+    ("banjo", "bookseller"),
+    ("beaming", "borderline"),
+    ("bedlamp", "bottomless"),
+    ("beehive", "Bradbury"),
+    ("beeswax", "bravado"),
+    ("befriend", "Brazilian"),
+    ("Belfast", "breakaway"),
+    ("berserk", "Burlington"),
+    ("billiard", "businessman"),
+    ("bison", "butterfat"),
+    ("blackjack", "Camelot"),
+    ("blockade", "candidate"),
+    ("blowtorch", "cannonball"),
+    ("bluebird", "Capricorn"),
+    ("bombast", "caravan"),
+    ("bookshelf", "caretaker"),
+    ("brackish", "celebrate"),
+    ("breadline", "cellulose"),
+    ("breakup", "certify"),
+    ("brickyard", "chambermaid"),
+    ("briefcase", "Cherokee"),
+    ("Burbank", "Chicago"),
+    ("button", "clergyman"),
+    ("buzzard", "coherence"),
+    ("cement", "combustion"),
+    ("chairlift", "commando"),
+    ("chatter", "company"),
+    ("checkup", "component"),
+    ("chisel", "concurrent"),
+    ("choking", "confidence"),
+    ("chopper", "conformist"),
+    ("Christmas", "congregate"),
+    ("clamshell", "consensus"),
+    ("classic", "consulting"),
+    ("classroom", "corporate"),
+    ("cleanup", "corrosion"),
+    ("clockwork", "councilman"),
+    ("cobra", "crossover"),
+    ("commence", "crucifix"),
+    ("concert", "cumbersome"),
+    ("cowbell", "customer"),
+    ("crackdown", "Dakota"),
+    ("cranky", "decadence"),
+    ("crowfoot", "December"),
+    ("crucial", "decimal"),
+    ("crumpled", "designing"),
+    ("crusade", "detector"),
+    ("cubic", "detergent"),
+    ("dashboard", "determine"),
+    ("deadbolt", "dictator"),
+    ("deckhand", "dinosaur"),
+    ("dogsled", "direction"),
+    ("dragnet", "disable"),
+    ("drainage", "disbelief"),
+    ("dreadful", "disruptive"),
+    ("drifter", "distortion"),
+    ("dropper", "document"),
+    ("drumbeat", "embezzle"),
+    ("drunken", "enchanting"),
+    ("Dupont", "enrollment"),
+    ("dwelling", "enterprise"),
+    ("eating", "equation"),
+    ("edict", "equipment"),
+    ("egghead", "escapade"),
+    ("eightball", "Eskimo"),
+    ("endorse", "everyday"),
+    ("endow", "examine"),
+    ("enlist", "existence"),
+    ("erase", "exodus"),
+    ("escape", "fascinate"),
+    ("exceed", "filament"),
+    ("eyeglass", "finicky"),
+    ("eyetooth", "forever"),
+    ("facial", "fortitude"),
+    ("fallout", "frequency"),
+    ("flagpole", "gadgetry"),
+    ("flatfoot", "Galveston"),
+    ("flytrap", "getaway"),
+    ("fracture", "glossary"),
+    ("framework", "gossamer"),
+    ("freedom", "graduate"),
+    ("frighten", "gravity"),
+    ("gazelle", "guitarist"),
+    ("Geiger", "hamburger"),
+    ("glitter", "Hamilton"),
+    ("glucose", "handiwork"),
+    ("goggles", "hazardous"),
+    ("goldfish", "headwaters"),
+    ("gremlin", "hemisphere"),
+    ("guidance", "hesitate"),
+    ("hamlet", "hideaway"),
+    ("highchair", "holiness"),
+    ("hockey", "hurricane"),
+    ("indoors", "hydraulic"),
+    ("indulge", "impartial"),
+    ("inverse", "impetus"),
+    ("involve", "inception"),
+    ("island", "indigo"),
+    ("jawbone", "inertia"),
+    ("keyboard", "infancy"),
+    ("kickoff", "inferno"),
+    ("kiwi", "informant"),
+    ("klaxon", "insincere"),
+    ("locale", "insurgent"),
+    ("lockup", "integrate"),
+    ("merit", "intention"),
+    ("minnow", "inventive"),
+    ("miser", "Istanbul"),
+    ("Mohawk", "Jamaica"),
+    ("mural", "Jupiter"),
+    ("music", "leprosy"),
+    ("necklace", "letterhead"),
+    ("Neptune", "liberty"),
+    ("newborn", "maritime"),
+    ("nightbird", "matchmaker"),
+    ("Oakland", "maverick"),
+    ("obtuse", "Medusa"),
+    ("offload", "megaton"),
+    ("optic", "microscope"),
+    ("orca", "microwave"),
+    ("payday", "midsummer"),
+    ("peachy", "millionaire"),
+    ("pheasant", "miracle"),
+    ("physique", "misnomer"),
+    ("playhouse", "molasses"),
+    ("Pluto", "molecule"),
+    ("preclude", "Montana"),
+    ("prefer", "monument"),
+    ("preshrunk", "mosquito"),
+    ("printer", "narrative"),
+    ("prowler", "nebula"),
+    ("pupil", "newsletter"),
+    ("puppy", "Norwegian"),
+    ("python", "October"),
+    ("quadrant", "Ohio"),
+    ("quiver", "onlooker"),
+    ("quota", "opulent"),
+    ("ragtime", "Orlando"),
+    ("ratchet", "outfielder"),
+    ("rebirth", "Pacific"),
+    ("reform", "pandemic"),
+    ("regain", "Pandora"),
+    ("reindeer", "paperweight"),
+    ("rematch", "paragon"),
+    ("repay", "paragraph"),
+    ("retouch", "paramount"),
+    ("revenge", "passenger"),
+    ("reward", "pedigree"),
+    ("rhythm", "Pegasus"),
+    ("ribcage", "penetrate"),
+    ("ringbolt", "perceptive"),
+    ("robust", "performance"),
+    ("rocker", "pharmacy"),
+    ("ruffled", "phonetic"),
+    ("sailboat", "photograph"),
+    ("sawdust", "pioneer"),
+    ("scallion", "pocketful"),
+    ("scenic", "politeness"),
+    ("scorecard", "positive"),
+    ("Scotland", "potato"),
+    ("seabird", "processor"),
+    ("select", "provincial"),
+    ("sentence", "proximate"),
+    ("shadow", "puberty"),
+    ("shamrock", "publisher"),
+    ("showgirl", "pyramid"),
+    ("skullcap", "quantity"),
+    ("skydive", "racketeer"),
+    ("slingshot", "rebellion"),
+    ("slowdown", "recipe"),
+    ("snapline", "recover"),
+    ("snapshot", "repellent"),
+    ("snowcap", "replica"),
+    ("snowslide", "reproduce"),
+    ("solo", "resistor"),
+    ("southward", "responsive"),
+    ("soybean", "retraction"),
+    ("spaniel", "retrieval"),
+    ("spearhead", "retrospect"),
+    ("spellbind", "revenue"),
+    ("spheroid", "revival"),
+    ("spigot", "revolver"),
+    ("spindle", "sandalwood"),
+    ("spyglass", "sardonic"),
+    ("stagehand", "Saturday"),
+    ("stagnate", "savagery"),
+    ("stairway", "scavenger"),
+    ("standard", "sensation"),
+    ("stapler", "sociable"),
+    ("steamship", "souvenir"),
+    ("sterling", "specialist"),
+    ("stockman", "speculate"),
+    ("stopwatch", "stethoscope"),
+    ("stormy", "stupendous"),
+    ("sugar", "supportive"),
+    ("surmount", "surrender"),
+    ("suspense", "suspicious"),
+    ("sweatband", "sympathy"),
+    ("swelter", "tambourine"),
+    ("tactics", "telephone"),
+    ("talon", "therapist"),
+    ("tapeworm", "tobacco"),
+    ("tempest", "tolerance"),
+    ("tiger", "tomorrow"),
+    ("tissue", "torpedo"),
+    ("tonic", "tradition"),
+    ("topmost", "travesty"),
+    ("tracker", "trombonist"),
+    ("transit", "truncated"),
+    ("trauma", "typewriter"),
+    ("treadmill", "ultimate"),
+    ("Trojan", "undaunted"),
+    ("trouble", "underfoot"),
+    ("tumor", "unicorn"),
+    ("tunnel", "unify"),
+    ("tycoon", "universe"),
+    ("uncut", "unravel"),
+    ("unearth", "upcoming"),
+    ("unwind", "vacancy"),
+    ("uproot", "vagabond"),
+    ("upset", "vertigo"),
+    ("upshot", "Virginia"),
+    ("vapor", "visitor"),
+    ("village", "vocalist"),
+    ("virus", "voyager"),
+    ("Vulcan", "warranty"),
+    ("waffle", "Waterloo"),
+    ("wallet", "whimsical"),
+    ("watchword", "Wichita"),
+    ("wayside", "Wilmington"),
+    ("willow", "Wyoming"),
+    ("woodlark", "yesteryear"),
+    ("Zulu", "Yucatan"),
+];
+
+/// Each byte becomes a word from the even list at even positions and from the
+/// odd list at odd positions so swapped or dropped words are noticed
+pub(crate) fn pgp_encode(data: &[u8]) -> String {
+    data.iter()
+        .enumerate()
+        .map(|(i, b)| match i % 2 {
+            0 => PGP_WORDS[*b as usize].0,
+            _ => PGP_WORDS[*b as usize].1,
+        })
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+pub(crate) fn pgp_decode(s: &str) -> Result<Vec<u8>, String> {
+    s.split_whitespace()
+        .enumerate()
+        .map(|(i, w)| {
+            let find = |odd: bool| {
+                PGP_WORDS.iter().position(|(e, o)| match odd {
+                    false => e.eq_ignore_ascii_case(w),
+                    true => o.eq_ignore_ascii_case(w),
+                })
+            };
+            match (find(i % 2 == 1), find(i % 2 == 0)) {
+                (Some(b), _) => Ok(b as u8),
+                (None, Some(_)) => Err(format!(
+                    "Word {} '{}' is out of place, a word may be missing or swapped",
+                    i + 1,
+                    w
+                )),
+                (None, None) => Err(format!("Unknown PGP word '{}'", w)),
+            }
+        })
+        .collect()
+}
+
+/// Proquints encode every 16 bits as a consonant-vowel-consonant-vowel-consonant word
+pub(crate) fn proquint_encode(data: &[u8]) -> Result<String, String> {
+    if !data.len().is_multiple_of(2) {
+        return Err("Proquints need an even number of bytes".to_string());
+    }
+    let words = data
+        .chunks(2)
+        .map(|c| {
+            let n = u16::from_be_bytes([c[0], c[1]]);
+            let c = |shift: u16| PROQUINT_CONSONANTS[usize::from(n >> shift & 0xf)] as char;
+            let v = |shift: u16| PROQUINT_VOWELS[usize::from(n >> shift & 0x3)] as char;
+            [c(12), v(10), c(6), v(4), c(0)].iter().collect::<String>()
+        })
+        .collect::<Vec<String>>();
+    Ok(words.join("-"))
+}
+
+pub(crate) fn proquint_decode(s: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    for w in s.split(|c: char| c == '-' || c.is_whitespace()) {
+        if w.is_empty() {
+            continue;
+        }
+        let invalid = || format!("Invalid proquint '{}'", w);
+        if w.len() != 5 {
+            return Err(invalid());
+        }
+        let mut n = 0u16;
+        for (i, c) in w.bytes().enumerate() {
+            let c = c.to_ascii_lowercase();
+            n = match i % 2 {
+                0 => match PROQUINT_CONSONANTS.iter().position(|p| *p == c) {
+                    Some(p) => n << 4 | p as u16,
+                    None => return Err(invalid()),
+                },
+                _ => match PROQUINT_VOWELS.iter().position(|p| *p == c) {
+                    Some(p) => n << 2 | p as u16,
+                    None => return Err(invalid()),
+                },
+            };
+        }
+        out.extend_from_slice(&n.to_be_bytes());
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pgp_words() {
+        let fingerprint = hex::decode("E58294F2E9A227486E8B061B31CC528FD7FA3F19").unwrap();
+        let words = "topmost Istanbul Pluto vagabond treadmill Pacific brackish dictator goldfish \
+                     Medusa afflict bravado chatter revolver Dupont midsummer stopwatch whimsical \
+                     cowbell bottomless";
+        assert_eq!(pgp_encode(&fingerprint), words);
+        assert_eq!(pgp_decode(words).unwrap(), fingerprint);
+        assert_eq!(pgp_decode("TOPMOST istanbul").unwrap(), [0xE5, 0x82]);
+
+        assert!(pgp_decode("Istanbul topmost")
+            .unwrap_err()
+            .contains("out of place"));
+        assert!(pgp_decode("topmost Pluto").is_err());
+        assert!(pgp_decode("topmost nope").is_err());
+    }
+
+    #[test]
+    fn proquints() {
+        assert_eq!(proquint_encode(&[127, 0, 0, 1]).unwrap(), "lusab-babad");
+        assert_eq!(proquint_encode(&[63, 84, 220, 193]).unwrap(), "gutih-tugad");
+        assert_eq!(proquint_decode("lusab-babad").unwrap(), [127, 0, 0, 1]);
+        assert_eq!(
+            proquint_decode("GUTIH tugad\n").unwrap(),
+            [63, 84, 220, 193]
+        );
+
+        assert!(proquint_encode(&[1, 2, 3]).is_err());
+        assert!(proquint_decode("lusa-babad").is_err());
+        assert!(proquint_decode("lusab-babac").is_err());
+    }
+}
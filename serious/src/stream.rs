@@ -0,0 +1,272 @@
+use crate::{radix_width, Encoding, Encoding::*};
+use std::io::{self, Read, Write};
+
+/// Bytes read from the inner reader at a time
+pub(crate) const CHUNK: usize = 8192;
+
+pub(crate) fn invalid(e: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Splits a stream into blocks that an encoding can process on their own and
+/// holds the output until the adapter passes it on. Hex, base64, blob and
+/// `radix:<n>:bytes` only keep back a partial block. Other encodings need all
+/// of their input at once so it's buffered until the end of the stream.
+pub(crate) struct Chunker {
+    encoding: Encoding,
+    decode: bool,
+    pending: Vec<u8>,
+    out: Vec<u8>,
+    pos: usize,
+}
+
+impl Chunker {
+    pub(crate) fn new(encoding: Encoding, decode: bool) -> Self {
+        Chunker {
+            encoding,
+            decode,
+            pending: Vec::new(),
+            out: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Input bytes in each block or `None` when the whole input is needed
+    fn block(&self) -> Option<usize> {
+        match (self.encoding, self.decode) {
+            (Blob, _) | (LowHex | UpHex | RadixBytes(_), false) => Some(1),
+            (LowHex | UpHex, true) => Some(2),
+            (Base64 | Base64Url, false) => Some(3),
+            (Base64 | Base64Url, true) => Some(4),
+            (RadixBytes(r), true) => Some(radix_width(r)),
+            _ => None,
+        }
+    }
+
+    fn process(&mut self, len: usize) -> io::Result<()> {
+        if len == 0 {
+            return Ok(());
+        }
+        let data = &self.pending[..len];
+        let res = match self.decode {
+            true => Encoding::decode_bytes(data, self.encoding),
+            false => Encoding::encode(data, self.encoding).try_into_vec(),
+        }
+        .map_err(invalid)?;
+        self.out.extend_from_slice(&res);
+        self.pending.drain(..len);
+        Ok(())
+    }
+
+    /// Process every complete block in `data` and keep the rest for later
+    pub(crate) fn update(&mut self, data: &[u8]) -> io::Result<()> {
+        // Hex and base64 are often wrapped across lines
+        match self.decode && self.encoding != Blob && self.block().is_some() {
+            true => self
+                .pending
+                .extend(data.iter().filter(|b| !b.is_ascii_whitespace())),
+            false => self.pending.extend_from_slice(data),
+        }
+        match self.block() {
+            Some(block) => self.process(self.pending.len() / block * block),
+            None => Ok(()),
+        }
+    }
+
+    /// Process whatever is left at the end of the stream
+    pub(crate) fn finish(&mut self) -> io::Result<()> {
+        self.process(self.pending.len())
+    }
+
+    /// Feed a read from the inner reader where an empty read is the end of the stream
+    pub(crate) fn feed(&mut self, read: &[u8]) -> io::Result<()> {
+        match read.is_empty() {
+            true => self.finish(),
+            false => self.update(read),
+        }
+    }
+
+    /// Output that hasn't been consumed yet
+    pub(crate) fn output(&self) -> &[u8] {
+        &self.out[self.pos..]
+    }
+
+    pub(crate) fn consume(&mut self, n: usize) {
+        self.pos += n;
+        if self.pos == self.out.len() {
+            self.out.clear();
+            self.pos = 0;
+        }
+    }
+}
+
+/// Encodes or decodes everything read from the inner reader
+pub struct Reader<R> {
+    inner: R,
+    chunker: Chunker,
+    input: Box<[u8]>,
+    eof: bool,
+}
+
+impl<R: Read> Reader<R> {
+    pub fn encoder(inner: R, encoding: Encoding) -> Self {
+        Reader::new(inner, Chunker::new(encoding, false))
+    }
+
+    pub fn decoder(inner: R, encoding: Encoding) -> Self {
+        Reader::new(inner, Chunker::new(encoding, true))
+    }
+
+    fn new(inner: R, chunker: Chunker) -> Self {
+        Reader {
+            inner,
+            chunker,
+            input: vec![0u8; CHUNK].into_boxed_slice(),
+            eof: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.chunker.output().is_empty() && !self.eof {
+            let n = self.inner.read(&mut self.input)?;
+            self.eof = n == 0;
+            self.chunker.feed(&self.input[..n])?;
+        }
+        let out = self.chunker.output();
+        let n = out.len().min(buf.len());
+        buf[..n].copy_from_slice(&out[..n]);
+        self.chunker.consume(n);
+        Ok(n)
+    }
+}
+
+/// Encodes or decodes everything written before passing it to the inner writer.
+/// Call [`Writer::finish`] when done or the last partial block is lost.
+pub struct Writer<W> {
+    inner: W,
+    chunker: Chunker,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn encoder(inner: W, encoding: Encoding) -> Self {
+        Writer {
+            inner,
+            chunker: Chunker::new(encoding, false),
+        }
+    }
+
+    pub fn decoder(inner: W, encoding: Encoding) -> Self {
+        Writer {
+            inner,
+            chunker: Chunker::new(encoding, true),
+        }
+    }
+
+    /// Write the last partial block, flush and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.chunker.finish()?;
+        self.inner.write_all(self.chunker.output())?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.chunker.update(buf)?;
+        self.inner.write_all(self.chunker.output())?;
+        self.chunker.consume(self.chunker.output().len());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn adapters() {
+        let mut rng = rand::thread_rng();
+        // Larger than CHUNK and not a multiple of any block size
+        let mut buffer = vec![0u8; CHUNK * 2 + 101];
+        rng.fill_bytes(buffer.as_mut_slice());
+        for e in [
+            Blob,
+            LowHex,
+            UpHex,
+            Base64,
+            Base64Url,
+            RadixBytes(8),
+            Base58,
+            Percent,
+        ] {
+            // Encodings without blocks are buffered whole and base58 is quadratic
+            let buffer = match e {
+                Base58 | Percent => &buffer[..300],
+                _ => &buffer[..],
+            };
+            let expected = Encoding::encode(buffer, e).into_vec();
+
+            let mut writer = Writer::encoder(Vec::new(), e);
+            for chunk in buffer.chunks(7) {
+                writer.write_all(chunk).unwrap();
+            }
+            assert_eq!(writer.finish().unwrap(), expected, "{}", e);
+
+            let mut encoded = Vec::new();
+            Reader::encoder(buffer, e)
+                .read_to_end(&mut encoded)
+                .unwrap();
+            assert_eq!(encoded, expected, "{}", e);
+
+            let mut decoded = Vec::new();
+            Reader::decoder(expected.as_slice(), e)
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, buffer, "{}", e);
+
+            let mut writer = Writer::decoder(Vec::new(), e);
+            for chunk in expected.chunks(5) {
+                writer.write_all(chunk).unwrap();
+            }
+            assert_eq!(writer.finish().unwrap(), buffer, "{}", e);
+        }
+
+        // Wrapped lines
+        let wrapped = Encoding::encode(&buffer, Base64)
+            .into_string()
+            .as_bytes()
+            .chunks(76)
+            .collect::<Vec<&[u8]>>()
+            .join(&b"\r\n"[..]);
+        let mut decoded = Vec::new();
+        Reader::decoder(wrapped.as_slice(), Base64)
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, buffer);
+
+        let mut decoded = Vec::new();
+        assert!(Reader::decoder(&b"QQ==QUJD"[..], Base64)
+            .read_to_end(&mut decoded)
+            .is_err());
+        assert!(Reader::decoder(&b"00fg"[..], LowHex)
+            .read_to_end(&mut decoded)
+            .is_err());
+        let writer = Writer::decoder(Vec::new(), LowHex);
+        assert!(writer.finish().unwrap().is_empty());
+        let mut writer = Writer::decoder(Vec::new(), LowHex);
+        writer.write_all(b"abc").unwrap();
+        assert!(writer.finish().is_err());
+    }
+}
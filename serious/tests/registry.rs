@@ -0,0 +1,97 @@
+// The registry is global so registering lives in its own test binary where
+// it can't change `Encoding::values` for the library's unit tests.
+
+use serious::{register, Codec, Encoding, Pipeline};
+
+/// Hex with the nibbles of each byte swapped
+struct SwappedHex;
+
+impl Codec for SwappedHex {
+    fn name(&self) -> &str {
+        "swappedhex"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["shx"]
+    }
+
+    fn encode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let swapped = data.iter().map(|b| b.rotate_left(4)).collect::<Vec<u8>>();
+        Ok(hex::encode(swapped).into_bytes())
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let data = hex::decode(data).map_err(|e| e.to_string())?;
+        Ok(data.iter().map(|b| b.rotate_left(4)).collect())
+    }
+}
+
+#[test]
+fn registry() {
+    let e = register(SwappedHex).unwrap();
+    assert_eq!(e, Encoding::parse("swappedhex").unwrap());
+    assert_eq!(e, Encoding::parse("shx").unwrap());
+    assert_eq!(e.to_string(), "swappedhex");
+    assert!(Encoding::values().contains(&e));
+
+    assert_eq!(Encoding::encode([0x12, 0xab], e).into_string(), "21ba");
+    assert_eq!(Encoding::decode("21ba", e).unwrap(), [0x12, 0xab]);
+    assert_eq!(
+        Encoding::recode("21ba", e, Encoding::LowHex).unwrap(),
+        "12ab"
+    );
+    let p = Pipeline::parse("hex | shx").unwrap();
+    assert_eq!(p.run("12ab").unwrap(), b"21ba");
+
+    assert!(register(SwappedHex).is_err());
+}
+
+/// Passes data through under any name and aliases
+struct Named(&'static str, Vec<&'static str>);
+
+impl Codec for Named {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &self.1
+    }
+
+    fn encode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        Ok(data.to_vec())
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        Ok(data.to_vec())
+    }
+}
+
+#[test]
+fn unreachable_names() {
+    for name in [
+        "",
+        "lowhex",
+        "base64url",
+        "a:b",
+        "a|b",
+        "a b",
+        "tab\t",
+        "gzip",
+        "gz",
+        "unzstd",
+        "gunzip",
+        "reverse",
+        "swap32",
+        "to",
+        "from",
+        "decode",
+        "custom01",
+        "radix7",
+        "eip1191x",
+    ] {
+        assert!(register(Named(name, vec![])).is_err(), "{:?}", name);
+        assert!(register(Named("plain", vec![name])).is_err(), "{:?}", name);
+    }
+    assert!(Encoding::parse("plain").is_err());
+}